@@ -1,10 +1,115 @@
+use clap::{Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
-use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
 use std::thread;
 use std::time::Duration;
 
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+const DEFAULT_SOCKET_PATH: &str = "/tmp/eww-battery.sock";
+const POLL_INTERVAL_SECS: u64 = 2;
+// A control client that sends no line within this long is dropped
+const CLIENT_READ_TIMEOUT_MS: u64 = 1000;
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Output format for each reading
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json, global = true)]
+    format: OutputFormat,
+
+    /// Unit for the reported `temp` field
+    #[arg(long, value_enum, default_value_t = TempUnit::C, global = true)]
+    temp_unit: TempUnit,
+
+    /// Battery temperature (°C) at which temp_level becomes "warm"
+    #[arg(long, default_value_t = 40, global = true)]
+    temp_warm: i32,
+
+    /// Battery temperature (°C) at which temp_level becomes "hot"
+    #[arg(long, default_value_t = 50, global = true)]
+    temp_hot: i32,
+
+    /// JSON file overriding battery glyphs, e.g. {"charging": "+", "empty": "!"}
+    #[arg(long, value_name = "FILE", value_parser = load_icons, global = true)]
+    icons: Option<BatteryIcons>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print a reading every 2 seconds
+    Listen {
+        /// Polls the smoothed `power_avg` averages over
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+        power_avg_window: u32,
+
+        /// Accept `refresh` commands on this socket (default path if no value is given)
+        #[arg(long, num_args = 0..=1, default_missing_value = DEFAULT_SOCKET_PATH)]
+        socket: Option<String>,
+    },
+    /// Make a listening instance print a fresh reading now, e.g. from a udev rule
+    Refresh {
+        #[arg(long, default_value = DEFAULT_SOCKET_PATH)]
+        socket: String,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum OutputFormat {
+    /// Full state as JSON (default)
+    Json,
+    /// Bare charge percentage, e.g. `85`
+    Percent,
+    /// Human readable line, e.g. `85% Discharging 2h 10m`
+    Plain,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum TempUnit {
+    /// Celsius (default)
+    C,
+    /// Fahrenheit
+    F,
+}
+
+/// Glyphs for `get_battery_icon`; fields missing from the icons file keep their default
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+struct BatteryIcons {
+    charging: String,
+    full: String,     // Full, or 90% and above
+    high: String,     // 70% and above
+    medium: String,   // 50% and above
+    low: String,      // 30% and above
+    critical: String, // 10% and above
+    empty: String,
+}
+
+impl Default for BatteryIcons {
+    fn default() -> Self {
+        Self {
+            charging: "󰂄".to_string(),
+            full: "󰁹".to_string(),
+            high: "󰂀".to_string(),
+            medium: "󰁾".to_string(),
+            low: "󰁼".to_string(),
+            critical: "󰁺".to_string(),
+            empty: "󰂎".to_string(),
+        }
+    }
+}
+
+fn load_icons(path: &str) -> Result<BatteryIcons, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    serde_json::from_str(&text).map_err(|e| format!("{}: {}", path, e))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct BatteryInfo {
     present: bool,
@@ -12,11 +117,13 @@ struct BatteryInfo {
     status: String,
     time: String,
     power: f64,
+    power_avg: f64, // Moving average of `power` in listen mode, else equal to it
     icon: String,
     health: u32,
     cycles: u32,
     voltage: String,
     temp: i32,
+    temp_level: String,
     design_capacity: String,
     current_capacity: String,
 }
@@ -29,67 +136,97 @@ impl Default for BatteryInfo {
             status: "N/A".to_string(),
             time: String::new(),
             power: 0.0,
+            power_avg: 0.0,
             icon: "".to_string(),
             health: 0,
             cycles: 0,
             voltage: "0.0V".to_string(),
             temp: 0,
+            temp_level: "normal".to_string(),
             design_capacity: "N/A".to_string(),
             current_capacity: "N/A".to_string(),
         }
     }
 }
 
-fn find_battery() -> Option<PathBuf> {
-    for bat in &["BAT0", "BAT1", "BAT2"] {
-        let path = PathBuf::from(format!("/sys/class/power_supply/{}", bat));
-        if path.exists() {
-            return Some(path);
-        }
-    }
-    None
+fn find_battery(base: &Path) -> Option<PathBuf> {
+    ["BAT0", "BAT1", "BAT2"]
+        .iter()
+        .map(|bat| base.join(bat))
+        .find(|path| path.exists())
 }
 
-fn read_file_u64(path: &PathBuf, file: &str) -> u64 {
+fn read_file_u64(path: &Path, file: &str) -> u64 {
     fs::read_to_string(path.join(file))
         .ok()
         .and_then(|s| s.trim().parse().ok())
         .unwrap_or(0)
 }
 
-fn read_file_string(path: &PathBuf, file: &str) -> String {
+fn read_file_string(path: &Path, file: &str) -> String {
     fs::read_to_string(path.join(file))
         .ok()
         .map(|s| s.trim().to_string())
         .unwrap_or_default()
 }
 
-fn get_battery_icon(status: &str, percent: u32) -> &'static str {
+// For batteries without a (non-zero) `capacity` file
+fn percent_from_levels(path: &Path) -> u32 {
+    for (now_file, full_file) in [("charge_now", "charge_full"), ("energy_now", "energy_full")] {
+        let full = read_file_u64(path, full_file);
+        if full > 0 {
+            let now = read_file_u64(path, now_file);
+            return ((now as f64 / full as f64) * 100.0).round().min(100.0) as u32;
+        }
+    }
+    0
+}
+
+fn get_battery_icon<'a>(status: &str, percent: u32, icons: &'a BatteryIcons) -> &'a str {
     if status == "Charging" {
-        "󰂄"
-    } else if status == "Full" {
-        "󰁹"
-    } else if percent >= 90 {
-        "󰁹"
+        &icons.charging
+    } else if status == "Full" || percent >= 90 {
+        &icons.full
     } else if percent >= 70 {
-        "󰂀"
+        &icons.high
     } else if percent >= 50 {
-        "󰁾"
+        &icons.medium
     } else if percent >= 30 {
-        "󰁼"
+        &icons.low
     } else if percent >= 10 {
-        "󰁺"
+        &icons.critical
+    } else {
+        &icons.empty
+    }
+}
+
+// Time to drain `amount` at `rate` (same unit per hour) as "Xh Ym"
+fn format_hours_minutes(amount: u64, rate: u64) -> String {
+    let hours = amount / rate;
+    let minutes = ((amount as f64 / rate as f64) - hours as f64) * 60.0;
+    format!("{}h {}m", hours, minutes as u32)
+}
+
+// Classifies a Celsius reading against the configured thresholds
+fn get_temp_level(celsius: i32, cli: &Cli) -> &'static str {
+    if celsius >= cli.temp_hot {
+        "hot"
+    } else if celsius >= cli.temp_warm {
+        "warm"
     } else {
-        "󰂎"
+        "normal"
     }
 }
 
-fn get_battery_info() -> BatteryInfo {
-    let Some(battery_path) = find_battery() else {
+fn get_battery_info(cli: &Cli, base: &Path) -> BatteryInfo {
+    let Some(battery_path) = find_battery(base) else {
         return BatteryInfo::default();
     };
 
-    let percent = read_file_u64(&battery_path, "capacity") as u32;
+    let percent = match read_file_u64(&battery_path, "capacity") {
+        0 => percent_from_levels(&battery_path),
+        capacity => capacity as u32,
+    };
     let status = read_file_string(&battery_path, "status");
     let power_now = read_file_u64(&battery_path, "power_now");
     let power = power_now as f64 / 1_000_000.0;
@@ -98,7 +235,12 @@ fn get_battery_info() -> BatteryInfo {
     let voltage = format!("{:.1}V", voltage_now as f64 / 1_000_000.0);
 
     let temp_raw = read_file_u64(&battery_path, "temp") as i64;
-    let temp = (temp_raw / 10) as i32;
+    let temp_celsius = (temp_raw / 10) as i32;
+    let temp_level = get_temp_level(temp_celsius, cli).to_string();
+    let temp = match cli.temp_unit {
+        TempUnit::C => temp_celsius,
+        TempUnit::F => temp_celsius * 9 / 5 + 32,
+    };
 
     let cycles = read_file_u64(&battery_path, "cycle_count") as u32;
 
@@ -140,24 +282,19 @@ fn get_battery_info() -> BatteryInfo {
         }
     };
 
-    let time = if current_now > 0 {
-        if status == "Discharging" {
-            let hours = current_capacity_raw / current_now;
-            let minutes = ((current_capacity_raw as f64 / current_now as f64) - hours as f64) * 60.0;
-            format!("{}h {}m", hours, minutes as u32)
-        } else if status == "Charging" {
-            let remaining = design_capacity_raw.saturating_sub(current_capacity_raw);
-            let hours = remaining / current_now;
-            let minutes = ((remaining as f64 / current_now as f64) - hours as f64) * 60.0;
-            format!("{}h {}m", hours, minutes as u32)
-        } else {
-            String::new()
-        }
-    } else {
-        String::new()
+    let time = match status.as_str() {
+        _ if current_now == 0 => String::new(),
+        "Discharging" => format_hours_minutes(current_capacity_raw, current_now),
+        "Charging" => format_hours_minutes(
+            design_capacity_raw.saturating_sub(current_capacity_raw),
+            current_now,
+        ),
+        _ => String::new(),
     };
 
-    let icon = get_battery_icon(&status, percent).to_string();
+    let default_icons = BatteryIcons::default();
+    let icons = cli.icons.as_ref().unwrap_or(&default_icons);
+    let icon = get_battery_icon(&status, percent, icons).to_string();
 
     BatteryInfo {
         present: true,
@@ -165,30 +302,263 @@ fn get_battery_info() -> BatteryInfo {
         status,
         time,
         power,
+        power_avg: power,
         icon,
         health,
         cycles,
         voltage,
         temp,
+        temp_level,
         design_capacity,
         current_capacity,
     }
 }
 
+// Exponential moving average weighted like a `window`-sample simple average
+fn power_ema(previous: Option<f64>, sample: f64, window: u32) -> f64 {
+    let alpha = 2.0 / (window as f64 + 1.0);
+    match previous {
+        Some(avg) => avg + alpha * (sample - avg),
+        None => sample,
+    }
+}
+
+fn render(info: &BatteryInfo, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Json => serde_json::to_string(info).unwrap(),
+        OutputFormat::Percent => info.percent.to_string(),
+        OutputFormat::Plain => {
+            let percent = format!("{}%", info.percent);
+            [percent.as_str(), info.status.as_str(), info.time.as_str()]
+                .iter()
+                .filter(|part| !part.is_empty())
+                .copied()
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+    }
+}
+
+// Control socket: one command per line, answered with "ok" or an error line.
+// Each `refresh` is forwarded to the listen loop.
+fn spawn_control_socket(socket_path: &str) -> std::io::Result<Receiver<()>> {
+    if Path::new(socket_path).exists() {
+        let _ = fs::remove_file(socket_path);
+    }
+    let listener = UnixListener::bind(socket_path)?;
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            // Clients are served one at a time, so a silent one must not stall the rest
+            let timeout = Duration::from_millis(CLIENT_READ_TIMEOUT_MS);
+            if stream.set_read_timeout(Some(timeout)).is_err() {
+                continue;
+            }
+            let mut line = String::new();
+            let Ok(reader) = stream.try_clone() else {
+                continue;
+            };
+            if BufReader::new(reader).read_line(&mut line).is_err() {
+                continue;
+            }
+            let reply = match line.trim() {
+                "refresh" => {
+                    if tx.send(()).is_err() {
+                        break;
+                    }
+                    "ok".to_string()
+                }
+                other => format!("error: unknown command {:?}", other),
+            };
+            let _ = writeln!(stream, "{}", reply);
+        }
+    });
+    Ok(rx)
+}
+
+fn send_refresh(socket_path: &str) -> Result<(), String> {
+    let mut stream = UnixStream::connect(socket_path)
+        .map_err(|e| format!("Cannot reach {}: {}", socket_path, e))?;
+    writeln!(stream, "refresh").map_err(|e| e.to_string())?;
+
+    let mut reply = String::new();
+    BufReader::new(stream)
+        .read_line(&mut reply)
+        .map_err(|e| e.to_string())?;
+    match reply.trim() {
+        "ok" => Ok(()),
+        other => Err(other.to_string()),
+    }
+}
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let listen_mode = args.len() > 1 && args[1] == "listen";
-
-    if listen_mode {
-        // Listen mode: poll every 2 seconds
-        loop {
-            let info = get_battery_info();
-            println!("{}", serde_json::to_string(&info).unwrap());
-            thread::sleep(Duration::from_secs(2));
+    let cli = Cli::parse();
+
+    match &cli.command {
+        Some(Command::Listen {
+            power_avg_window,
+            socket,
+        }) => {
+            let refresh = match socket.as_deref().map(spawn_control_socket).transpose() {
+                Ok(refresh) => refresh,
+                Err(e) => {
+                    eprintln!("Error: cannot listen on control socket: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            // Listen mode: poll every 2 seconds, or sooner when asked to refresh
+            let interval = Duration::from_secs(POLL_INTERVAL_SECS);
+            let mut power_avg = None;
+            loop {
+                let mut info = get_battery_info(&cli, Path::new(POWER_SUPPLY_DIR));
+                info.power_avg = power_ema(power_avg, info.power, *power_avg_window);
+                power_avg = Some(info.power_avg);
+                println!("{}", render(&info, cli.format));
+
+                match refresh.as_ref().map(|rx| rx.recv_timeout(interval)) {
+                    Some(Ok(())) | Some(Err(RecvTimeoutError::Timeout)) => {}
+                    Some(Err(RecvTimeoutError::Disconnected)) | None => thread::sleep(interval),
+                }
+            }
         }
-    } else {
-        // One-shot mode
-        let info = get_battery_info();
-        println!("{}", serde_json::to_string(&info).unwrap());
+        Some(Command::Refresh { socket }) => {
+            if let Err(e) = send_refresh(socket) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        None => {
+            // One-shot mode
+            let info = get_battery_info(&cli, Path::new(POWER_SUPPLY_DIR));
+            println!("{}", render(&info, cli.format));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cli() -> Cli {
+        Cli::parse_from(["eww-battery"])
+    }
+
+    fn fake_battery(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let root = tempfile::tempdir().unwrap();
+        let battery = root.path().join("BAT0");
+        fs::create_dir(&battery).unwrap();
+        for (name, value) in files {
+            fs::write(battery.join(name), format!("{}\n", value)).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn charge_based_battery() {
+        let root = fake_battery(&[
+            ("capacity", "75"),
+            ("status", "Discharging"),
+            ("charge_full_design", "5000000"),
+            ("charge_full", "4000000"),
+            ("current_now", "1000000"),
+        ]);
+
+        let info = get_battery_info(&cli(), root.path());
+
+        assert!(info.present);
+        assert_eq!(info.percent, 75);
+        assert_eq!(info.health, 80);
+        assert_eq!(info.time, "4h 0m");
+        assert_eq!(info.design_capacity, "5000 mAh");
+    }
+
+    #[test]
+    fn energy_based_battery() {
+        let root = fake_battery(&[
+            ("capacity", "40"),
+            ("status", "Charging"),
+            ("energy_full_design", "60000000"),
+            ("energy_full", "45000000"),
+            ("power_now", "10000000"),
+        ]);
+
+        let info = get_battery_info(&cli(), root.path());
+
+        assert!(info.present);
+        assert_eq!(info.percent, 40);
+        assert_eq!(info.health, 75);
+        assert_eq!(info.time, "1h 30m");
+        assert_eq!(info.power, 10.0);
+    }
+
+    #[test]
+    fn percent_without_capacity_file() {
+        let root = fake_battery(&[
+            ("status", "Discharging"),
+            ("charge_now", "3000000"),
+            ("charge_full", "4000000"),
+        ]);
+        assert_eq!(get_battery_info(&cli(), root.path()).percent, 75);
+
+        let root = fake_battery(&[
+            ("capacity", "0"),
+            ("status", "Discharging"),
+            ("energy_now", "20000000"),
+            ("energy_full", "50000000"),
+        ]);
+        assert_eq!(get_battery_info(&cli(), root.path()).percent, 40);
+    }
+
+    #[test]
+    fn icons_file_overrides_defaults() {
+        let root = fake_battery(&[("capacity", "5"), ("status", "Discharging")]);
+        let icons = root.path().join("icons.json");
+        fs::write(&icons, r#"{"empty": "E"}"#).unwrap();
+        let cli = Cli::parse_from(["eww-battery", "--icons", icons.to_str().unwrap()]);
+
+        let info = get_battery_info(&cli, root.path());
+        assert_eq!(info.icon, "E");
+
+        fs::write(root.path().join("BAT0/capacity"), "95\n").unwrap();
+        let info = get_battery_info(&cli, root.path());
+        assert_eq!(info.icon, BatteryIcons::default().full);
+    }
+
+    #[test]
+    fn power_avg_smooths_jitter() {
+        let first = power_ema(None, 10.0, 3);
+        assert_eq!(first, 10.0);
+
+        let second = power_ema(Some(first), 20.0, 3);
+        assert_eq!(second, 15.0);
+
+        // A window of one follows the instantaneous reading
+        assert_eq!(power_ema(Some(second), 4.0, 1), 4.0);
+    }
+
+    #[test]
+    fn missing_battery() {
+        let root = tempfile::tempdir().unwrap();
+
+        let info = get_battery_info(&cli(), root.path());
+
+        assert!(!info.present);
+        assert_eq!(info.percent, 0);
+        assert_eq!(info.health, 0);
+        assert_eq!(info.time, "");
+    }
+
+    #[test]
+    fn refresh_command_reaches_listen_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("battery.sock");
+        let socket = socket.to_str().unwrap();
+
+        let refresh = spawn_control_socket(socket).unwrap();
+        send_refresh(socket).unwrap();
+
+        assert!(refresh.recv_timeout(Duration::from_secs(1)).is_ok());
     }
 }
@@ -1,25 +1,247 @@
+use clap::{Parser, Subcommand};
 use serde::Serialize;
-use zbus::{Connection, Result, zvariant::{OwnedObjectPath, OwnedValue}};
+use zbus::{Connection, Result, zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value}};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const ADAPTER_PATH: &str = "/org/bluez/hci0";
+// The system bus or bluetoothd may still be starting when the bar launches us
+const STATUS_ATTEMPTS: u32 = 3;
+const STATUS_BACKOFF_MS: u64 = 500;
+
+#[derive(Parser)]
+#[command(name = "eww-bluetooth", about = "Bluetooth status for eww")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Connect the most recently connected device again
+    ReconnectLast,
+    /// Trust a paired device (XX:XX:XX:XX:XX:XX)
+    Trust { address: String },
+    /// Stop trusting a paired device
+    Untrust { address: String },
+    /// Remove a device and its pairing from the adapter
+    Remove { address: String },
+}
 
 #[derive(Serialize)]
 struct Bluetooth {
+    available: bool, // False when the system bus or BlueZ can't be reached
     enabled: bool,
     connected: bool,
     device: String,
+    type_icon: String, // Glyph for the connected device's kind, empty when none
+    profile: String,
+    codec: String,
+    error: String, // Empty unless BlueZ couldn't be queried
 }
 
+type ManagedObjects = HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>>;
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let status = get_bluetooth_status().await.unwrap_or(Bluetooth {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Some(Commands::ReconnectLast) => reconnect_last().await,
+        Some(Commands::Trust { address }) => set_trusted(&address, true).await,
+        Some(Commands::Untrust { address }) => set_trusted(&address, false).await,
+        Some(Commands::Remove { address }) => remove_device(&address).await,
+        None => Ok(()),
+    };
+    if let Err(e) = result {
+        eprintln!("eww-bluetooth: {}", e);
+        std::process::exit(1);
+    }
+
+    let status = get_status_with_retry().await.unwrap_or_else(|e| Bluetooth {
+        available: !is_unreachable(&e),
         enabled: false,
         connected: false,
         device: String::new(),
+        type_icon: String::new(),
+        profile: String::new(),
+        codec: String::new(),
+        error: e.to_string(),
     });
     println!("{}", serde_json::to_string(&status).unwrap());
     Ok(())
 }
 
+fn get_last_device_path() -> Option<PathBuf> {
+    let state_dir = dirs::state_dir()
+        .or_else(dirs::cache_dir)?
+        .join("eww-bluetooth");
+    std::fs::create_dir_all(&state_dir).ok()?;
+    Some(state_dir.join("last-device"))
+}
+
+/// Remember the connected device so `reconnect-last` can find it later.
+/// Runs on every poll, so the file is only written when the device changes.
+fn record_last_device(device: &OwnedObjectPath) {
+    if let Some(path) = get_last_device_path() {
+        let recorded = std::fs::read_to_string(&path).unwrap_or_default();
+        if recorded.trim() != device.as_str() {
+            let _ = std::fs::write(path, device.as_str());
+        }
+    }
+}
+
+async fn get_managed_objects(connection: &Connection) -> Result<ManagedObjects> {
+    let object_manager = zbus::Proxy::new(
+        connection,
+        "org.bluez",
+        "/",
+        "org.freedesktop.DBus.ObjectManager",
+    )
+    .await?;
+
+    let response = object_manager.call_method("GetManagedObjects", &()).await?;
+    Ok(response.body().deserialize().unwrap_or_default())
+}
+
+async fn reconnect_last() -> Result<()> {
+    let device = get_last_device_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| zbus::Error::Failure("no last connected device recorded".to_string()))?;
+
+    let connection = Connection::system().await?;
+    let objects = get_managed_objects(&connection).await?;
+    let paired = objects
+        .iter()
+        .find(|(path, _)| path.as_str() == device)
+        .and_then(|(_, interfaces)| interfaces.get("org.bluez.Device1"))
+        .and_then(|props| props.get("Paired"))
+        .and_then(|v| v.downcast_ref::<bool>().ok())
+        .unwrap_or(false);
+    if !paired {
+        return Err(zbus::Error::Failure(format!(
+            "last device {} is no longer paired",
+            device
+        )));
+    }
+
+    let proxy = zbus::Proxy::new(
+        &connection,
+        "org.bluez",
+        device.as_str(),
+        "org.bluez.Device1",
+    )
+    .await?;
+    proxy.call_method("Connect", &()).await?;
+    Ok(())
+}
+
+/// Object path BlueZ uses for `address` on our adapter
+fn device_object_path(address: &str) -> String {
+    format!(
+        "{}/dev_{}",
+        ADAPTER_PATH,
+        address.to_uppercase().replace(':', "_")
+    )
+}
+
+/// Resolve `address` to a known device's object path, erroring with the path
+/// that was expected when the address is malformed or unknown
+async fn find_device(connection: &Connection, address: &str) -> Result<String> {
+    let path = device_object_path(address);
+    let valid = address.split(':').count() == 6
+        && address
+            .split(':')
+            .all(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_hexdigit()));
+    if !valid {
+        return Err(zbus::Error::Failure(format!(
+            "invalid address '{}' (expected XX:XX:XX:XX:XX:XX for {})",
+            address, path
+        )));
+    }
+
+    let objects = get_managed_objects(connection).await?;
+    let known = objects
+        .iter()
+        .any(|(p, interfaces)| p.as_str() == path && interfaces.contains_key("org.bluez.Device1"));
+    if !known {
+        return Err(zbus::Error::Failure(format!("no device at {}", path)));
+    }
+    Ok(path)
+}
+
+async fn set_trusted(address: &str, trusted: bool) -> Result<()> {
+    let connection = Connection::system().await?;
+    let device = find_device(&connection, address).await?;
+
+    let proxy = zbus::Proxy::new(
+        &connection,
+        "org.bluez",
+        device.as_str(),
+        "org.freedesktop.DBus.Properties",
+    )
+    .await?;
+    proxy
+        .call_method(
+            "Set",
+            &("org.bluez.Device1", "Trusted", Value::from(trusted)),
+        )
+        .await?;
+    Ok(())
+}
+
+async fn remove_device(address: &str) -> Result<()> {
+    let connection = Connection::system().await?;
+    let device = find_device(&connection, address).await?;
+
+    let adapter =
+        zbus::Proxy::new(&connection, "org.bluez", ADAPTER_PATH, "org.bluez.Adapter1").await?;
+    adapter
+        .call_method("RemoveDevice", &(ObjectPath::try_from(device.as_str())?,))
+        .await?;
+    Ok(())
+}
+
+/// Whether `e` means there is no Bluetooth stack to talk to, as opposed to
+/// BlueZ answering with an error
+fn is_unreachable(e: &zbus::Error) -> bool {
+    const MISSING_SERVICE: [&str; 2] = [
+        "org.freedesktop.DBus.Error.ServiceUnknown",
+        "org.freedesktop.DBus.Error.NameHasNoOwner",
+    ];
+    match e {
+        zbus::Error::Address(_)
+        | zbus::Error::InputOutput(_)
+        | zbus::Error::Connection(..)
+        | zbus::Error::Handshake(_) => true,
+        zbus::Error::MethodError(name, _, _) => MISSING_SERVICE.contains(&name.as_str()),
+        zbus::Error::FDO(e) => matches!(
+            **e,
+            zbus::fdo::Error::ServiceUnknown(_) | zbus::fdo::Error::NameHasNoOwner(_)
+        ),
+        _ => false,
+    }
+}
+
+/// Status, retried with a doubling backoff while the stack is unreachable
+async fn get_status_with_retry() -> Result<Bluetooth> {
+    let mut delay = Duration::from_millis(STATUS_BACKOFF_MS);
+    let mut attempt = 1;
+    loop {
+        match get_bluetooth_status().await {
+            Err(e) if is_unreachable(&e) && attempt < STATUS_ATTEMPTS => {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
 async fn get_bluetooth_status() -> Result<Bluetooth> {
     let connection = Connection::system().await?;
 
@@ -31,7 +253,7 @@ async fn get_bluetooth_status() -> Result<Bluetooth> {
     let proxy = zbus::Proxy::new(
         &connection,
         "org.bluez",
-        "/org/bluez/hci0",
+        ADAPTER_PATH,
         "org.freedesktop.DBus.Properties",
     )
     .await?;
@@ -51,40 +273,28 @@ async fn get_bluetooth_status() -> Result<Bluetooth> {
                 .map(|(v,)| v)
                 .unwrap_or(false)
         }
+        Err(e) if is_unreachable(&e) => return Err(e),
         Err(_) => false,
     };
 
     if !powered {
         return Ok(Bluetooth {
+            available: true,
             enabled: false,
             connected: false,
             device: String::new(),
+            type_icon: String::new(),
+            profile: String::new(),
+            codec: String::new(),
+            error: String::new(),
         });
     }
 
     // Get managed objects to find connected devices
-    let object_manager = zbus::Proxy::new(
-        &connection,
-        "org.bluez",
-        "/",
-        "org.freedesktop.DBus.ObjectManager",
-    )
-    .await?;
-
-    let response = object_manager
-        .call_method("GetManagedObjects", &())
-        .await
-        .ok();
-
-    let objects: HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>> =
-        if let Some(resp) = response.as_ref() {
-            resp.body().deserialize().unwrap_or_default()
-        } else {
-            HashMap::new()
-        };
+    let objects = get_managed_objects(&connection).await.unwrap_or_default();
 
     // Find first connected device
-    for (path, interfaces) in objects {
+    for (path, interfaces) in &objects {
         if let Some(device_props) = interfaces.get("org.bluez.Device1") {
             if let Some(connected) = device_props.get("Connected") {
                 if let Ok(true) = connected.downcast_ref::<bool>() {
@@ -96,15 +306,29 @@ async fn get_bluetooth_status() -> Result<Bluetooth> {
                         .unwrap_or_else(|| {
                             path.to_string()
                                 .split('/')
-                                .last()
+                                .next_back()
                                 .unwrap_or("Unknown")
                                 .to_string()
                         });
 
+                    let type_icon = device_props
+                        .get("Icon")
+                        .and_then(|v| v.downcast_ref::<String>().ok())
+                        .map(|icon| type_icon(&icon))
+                        .unwrap_or(DEFAULT_TYPE_ICON)
+                        .to_string();
+                    let (profile, codec) = transport_info(&objects, path);
+                    record_last_device(path);
+
                     return Ok(Bluetooth {
+                        available: true,
                         enabled: true,
                         connected: true,
                         device: device_name,
+                        type_icon,
+                        profile,
+                        codec,
+                        error: String::new(),
                     });
                 }
             }
@@ -113,8 +337,112 @@ async fn get_bluetooth_status() -> Result<Bluetooth> {
 
     // Powered but no connected devices
     Ok(Bluetooth {
+        available: true,
         enabled: true,
         connected: false,
         device: String::new(),
+        type_icon: String::new(),
+        profile: String::new(),
+        codec: String::new(),
+        error: String::new(),
     })
 }
+
+const DEFAULT_TYPE_ICON: &str = "󰂯";
+
+/// Glyph for a device's BlueZ `Icon` (a freedesktop icon name such as
+/// "audio-headphones"), falling back to the Bluetooth logo
+fn type_icon(icon: &str) -> &'static str {
+    match icon {
+        "audio-headphones" => "󰋋",
+        "audio-headset" => "󰋎",
+        "audio-card" => "󰓃",
+        "input-mouse" => "󰍽",
+        "input-keyboard" => "󰌌",
+        "input-gaming" => "󰊖",
+        "input-tablet" => "󰓶",
+        "phone" => "󰏲",
+        "computer" => "󰌢",
+        "video-display" => "󰍹",
+        "camera-photo" | "camera-video" => "󰄀",
+        "printer" => "󰐪",
+        _ => DEFAULT_TYPE_ICON,
+    }
+}
+
+/// Profile name for a BlueZ media transport UUID
+fn profile_from_uuid(uuid: &str) -> &'static str {
+    match uuid.get(..8).map(str::to_lowercase).as_deref() {
+        Some("0000110a") | Some("0000110b") => "a2dp",
+        Some("0000111e") | Some("0000111f") => "hfp",
+        Some("00001108") | Some("00001112") => "hsp",
+        _ => "",
+    }
+}
+
+/// Codec name from a transport's codec id (and vendor configuration for A2DP)
+fn codec_name(profile: &str, codec: u8, configuration: &[u8]) -> String {
+    let name = match (profile, codec) {
+        ("a2dp", 0x00) => "sbc",
+        ("a2dp", 0x01) => "mpeg",
+        ("a2dp", 0x02) => "aac",
+        ("a2dp", 0x04) => "atrac",
+        ("a2dp", 0xff) if configuration.len() >= 6 => {
+            // Vendor codecs: 32-bit vendor id followed by a 16-bit codec id
+            let vendor = u32::from_le_bytes([
+                configuration[0],
+                configuration[1],
+                configuration[2],
+                configuration[3],
+            ]);
+            let id = u16::from_le_bytes([configuration[4], configuration[5]]);
+            match (vendor, id) {
+                (0x004f, 0x0001) => "aptx",
+                (0x00d7, 0x0024) => "aptx-hd",
+                (0x012d, 0x00aa) => "ldac",
+                _ => "vendor",
+            }
+        }
+        ("hfp" | "hsp", 0x01) => "cvsd",
+        ("hfp" | "hsp", 0x02) => "msbc",
+        _ => "",
+    };
+    name.to_string()
+}
+
+/// Profile and codec of a device's active media transport. PipeWire's native
+/// HFP/HSP backend doesn't go through BlueZ transports, so both may be empty.
+fn transport_info(objects: &ManagedObjects, device: &OwnedObjectPath) -> (String, String) {
+    for interfaces in objects.values() {
+        let Some(transport) = interfaces.get("org.bluez.MediaTransport1") else {
+            continue;
+        };
+        let owner = transport
+            .get("Device")
+            .and_then(|v| v.downcast_ref::<ObjectPath>().ok());
+        if owner.as_ref() != Some(&**device) {
+            continue;
+        }
+
+        let profile = transport
+            .get("UUID")
+            .and_then(|v| v.downcast_ref::<String>().ok())
+            .map(|uuid| profile_from_uuid(&uuid))
+            .unwrap_or_default();
+        let codec = transport
+            .get("Codec")
+            .and_then(|v| v.downcast_ref::<u8>().ok());
+        let configuration: Vec<u8> = transport
+            .get("Configuration")
+            .and_then(|v| v.try_clone().ok())
+            .and_then(|v| Vec::try_from(v).ok())
+            .unwrap_or_default();
+
+        let codec = codec
+            .map(|c| codec_name(profile, c, &configuration))
+            .unwrap_or_default();
+        return (profile.to_string(), codec);
+    }
+
+    (String::new(), String::new())
+}
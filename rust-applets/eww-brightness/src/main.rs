@@ -3,28 +3,61 @@ use serde::Serialize;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const BACKLIGHT_DIR: &str = "/sys/class/backlight";
+// Last gammastep temperature as "<unix secs> <kelvin>", kelvin empty when unknown
+const COLOR_TEMP_CACHE: &str = "/tmp/eww-brightness-color-temp";
+const COLOR_TEMP_REFRESH_SECS: u64 = 60;
+// `gammastep -p` may wait on a location provider such as geoclue
+const GAMMASTEP_TIMEOUT_MS: u64 = 1000;
 
 #[derive(Serialize)]
 struct Brightness {
     percent: u32,
+    // What gammastep computes for now from its config, refreshed at most once a
+    // minute; not read back from the running instance. Null under wlsunset.
+    color_temp_k: Option<u32>,
+    dpms_off: bool,
 }
 
-fn find_brightness_path() -> Option<PathBuf> {
-    let backlight_dir = Path::new("/sys/class/backlight");
-    if let Ok(entries) = fs::read_dir(backlight_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.join("brightness").exists() {
-                return Some(path);
-            }
+impl Brightness {
+    fn current() -> Self {
+        Brightness {
+            percent: get_brightness(Path::new(BACKLIGHT_DIR)),
+            color_temp_k: get_color_temp(),
+            dpms_off: is_dpms_off(),
         }
     }
-    None
 }
 
-fn get_brightness() -> u32 {
-    if let Some(path) = find_brightness_path() {
+// Same preference as the kernel/systemd: firmware interfaces beat
+// platform drivers, which beat raw GPU registers
+fn backlight_rank(path: &Path) -> u8 {
+    match fs::read_to_string(path.join("type")).unwrap_or_default().trim() {
+        "firmware" => 0,
+        "platform" => 1,
+        "raw" => 2,
+        _ => 3,
+    }
+}
+
+fn find_brightness_path(backlight_dir: &Path) -> Option<PathBuf> {
+    let mut devices: Vec<PathBuf> = fs::read_dir(backlight_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join("brightness").exists())
+        .collect();
+    devices.sort_by_key(|path| (backlight_rank(path), path.clone()));
+    devices.into_iter().next()
+}
+
+fn get_brightness(backlight_dir: &Path) -> u32 {
+    if let Some(path) = find_brightness_path(backlight_dir) {
         let max_path = path.join("max_brightness");
         let cur_path = path.join("brightness");
 
@@ -40,6 +73,120 @@ fn get_brightness() -> u32 {
     1
 }
 
+fn is_process_running(name: &str) -> bool {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        fs::read_to_string(entry.path().join("comm"))
+            .map(|comm| comm.trim() == name)
+            .unwrap_or(false)
+    })
+}
+
+// gammastep -p prints the period and temperature it would apply right now,
+// using the same config file as the running instance
+fn get_gammastep_temp() -> Option<u32> {
+    let mut child = Command::new("gammastep")
+        .arg("-p")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .ok()?;
+
+    let deadline = Instant::now() + Duration::from_millis(GAMMASTEP_TIMEOUT_MS);
+    while child.try_wait().ok()?.is_none() {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    let mut text = String::new();
+    child.stdout.take()?.read_to_string(&mut text).ok()?;
+    if text.is_empty() {
+        child.stderr.take()?.read_to_string(&mut text).ok()?;
+    }
+    text.lines()
+        .find_map(|line| line.trim().strip_prefix("Color temperature:"))
+        .and_then(|temp| temp.trim().trim_end_matches('K').parse().ok())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Brightness changes come in bursts of events, so gammastep is asked at most
+// once per COLOR_TEMP_REFRESH_SECS and the answer is shared through a file
+fn cached_gammastep_temp() -> Option<u32> {
+    let now = unix_now();
+    let cached = fs::read_to_string(COLOR_TEMP_CACHE).ok().and_then(|text| {
+        let (at, temp) = text.trim().split_once(' ').unwrap_or((text.trim(), ""));
+        let at: u64 = at.parse().ok()?;
+        (now.saturating_sub(at) < COLOR_TEMP_REFRESH_SECS).then(|| temp.parse().ok())
+    });
+    if let Some(temp) = cached {
+        return temp;
+    }
+
+    let temp = get_gammastep_temp();
+    let text = format!("{} {}", now, temp.map(|t| t.to_string()).unwrap_or_default());
+    let _ = fs::write(COLOR_TEMP_CACHE, text);
+    temp
+}
+
+fn get_color_temp() -> Option<u32> {
+    if is_process_running("gammastep") {
+        return cached_gammastep_temp();
+    }
+    // wlsunset has no control interface to query the applied temperature,
+    // so it reports null like when no night-light tool is running
+    None
+}
+
+fn query_json(program: &str, args: &[&str]) -> Option<serde_json::Value> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+// The screen counts as blanked only when every output is off, which is what
+// idle daemons do; an unqueryable compositor reports false
+fn is_dpms_off() -> bool {
+    let outputs = if let Some(outputs) = query_json("swaymsg", &["-t", "get_outputs", "-r"]) {
+        outputs
+            .as_array()
+            .map(|list| {
+                list.iter()
+                    .filter(|o| o["active"].as_bool().unwrap_or(true))
+                    // Newer sway reports `power`, older releases only `dpms`
+                    .map(|o| o["power"].as_bool().or(o["dpms"].as_bool()).unwrap_or(true))
+                    .collect::<Vec<bool>>()
+            })
+            .unwrap_or_default()
+    } else if let Some(monitors) = query_json("hyprctl", &["monitors", "-j"]) {
+        monitors
+            .as_array()
+            .map(|list| {
+                list.iter()
+                    .map(|m| m["dpmsStatus"].as_bool().unwrap_or(true))
+                    .collect::<Vec<bool>>()
+            })
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    !outputs.is_empty() && outputs.iter().all(|on| !on)
+}
+
 fn set_brightness_up() {
     let _ = Command::new("brightnessctl")
         .arg("set")
@@ -48,7 +195,7 @@ fn set_brightness_up() {
 }
 
 fn set_brightness_down() {
-    let current = get_brightness();
+    let current = get_brightness(Path::new(BACKLIGHT_DIR));
     if current > 1 {
         let _ = Command::new("brightnessctl")
             .arg("set")
@@ -63,11 +210,11 @@ fn main() {
     match args.get(1).map(|s| s.as_str()) {
         Some("listen") => {
             // Output current brightness
-            let brightness = Brightness { percent: get_brightness() };
+            let brightness = Brightness::current();
             println!("{}", serde_json::to_string(&brightness).unwrap());
 
             // Setup inotify to watch brightness file
-            if let Some(path) = find_brightness_path() {
+            if let Some(path) = find_brightness_path(Path::new(BACKLIGHT_DIR)) {
                 let brightness_file = path.join("brightness");
 
                 if let Ok(mut inotify) = Inotify::init() {
@@ -75,7 +222,7 @@ fn main() {
                         let mut buffer = [0; 1024];
                         loop {
                             if inotify.read_events_blocking(&mut buffer).is_ok() {
-                                let brightness = Brightness { percent: get_brightness() };
+                                let brightness = Brightness::current();
                                 println!("{}", serde_json::to_string(&brightness).unwrap());
                             }
                         }
@@ -90,8 +237,64 @@ fn main() {
             set_brightness_down();
         }
         _ => {
-            let brightness = Brightness { percent: get_brightness() };
+            let brightness = Brightness::current();
             println!("{}", serde_json::to_string(&brightness).unwrap());
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_backlight(root: &Path, name: &str, kind: &str, cur: &str, max: &str) {
+        let device = root.join(name);
+        fs::create_dir(&device).unwrap();
+        fs::write(device.join("type"), format!("{}\n", kind)).unwrap();
+        fs::write(device.join("brightness"), format!("{}\n", cur)).unwrap();
+        fs::write(device.join("max_brightness"), format!("{}\n", max)).unwrap();
+    }
+
+    #[test]
+    fn percent_is_rounded() {
+        let root = tempfile::tempdir().unwrap();
+        fake_backlight(root.path(), "intel_backlight", "raw", "333", "1000");
+
+        assert_eq!(get_brightness(root.path()), 33);
+    }
+
+    #[test]
+    fn percent_is_floored_at_one() {
+        let root = tempfile::tempdir().unwrap();
+        fake_backlight(root.path(), "intel_backlight", "raw", "0", "1000");
+
+        assert_eq!(get_brightness(root.path()), 1);
+    }
+
+    #[test]
+    fn zero_max_brightness_is_guarded() {
+        let root = tempfile::tempdir().unwrap();
+        fake_backlight(root.path(), "intel_backlight", "raw", "50", "0");
+
+        assert_eq!(get_brightness(root.path()), 1);
+    }
+
+    #[test]
+    fn missing_backlight_falls_back() {
+        let root = tempfile::tempdir().unwrap();
+
+        assert_eq!(find_brightness_path(root.path()), None);
+        assert_eq!(get_brightness(root.path()), 1);
+    }
+
+    #[test]
+    fn firmware_backlight_is_preferred() {
+        let root = tempfile::tempdir().unwrap();
+        fake_backlight(root.path(), "amdgpu_bl0", "raw", "10", "100");
+        fake_backlight(root.path(), "acpi_video0", "firmware", "80", "100");
+
+        let path = find_brightness_path(root.path()).unwrap();
+        assert!(path.ends_with("acpi_video0"));
+        assert_eq!(get_brightness(root.path()), 80);
+    }
+}
@@ -1,13 +1,63 @@
-use serde::Serialize;
-use std::env;
-use swayipc::{Connection, Event, EventType};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use swayipc::{Connection, Event, EventType, Input};
 
-#[derive(Serialize)]
+const RECONNECT_DELAY_MS: u64 = 1000;
+const DEFAULT_SOCKET_PATH: &str = "/tmp/eww-keyboard.sock";
+
+#[derive(Parser)]
+#[command(name = "eww-keyboard", about = "Keyboard layout for eww")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Output JSON, or only the layout code (e.g. "us")
+    #[arg(long, global = true, value_enum, default_value_t = Format::Json)]
+    format: Format,
+
+    /// Socket the daemon serves the cached layout on
+    #[arg(long, global = true, default_value = DEFAULT_SOCKET_PATH)]
+    socket: String,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the layout now and again on every change
+    Listen,
+    /// Keep one sway subscription and answer one-shot queries over the socket
+    Daemon,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+    Code,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 struct KeyboardLayout {
-    layout: String,
+    layout: String,       // Raw xkb code, e.g. "uk" for Ukrainian
+    country_code: String, // Lowercase ISO 3166-1 alpha-2 matching the flag, e.g. "ua"
     icon: String,
 }
 
+impl KeyboardLayout {
+    fn from_code(code: String) -> Self {
+        let country_code = code_to_country(&code);
+        let icon = code_to_flag(code.clone());
+        KeyboardLayout {
+            layout: code,
+            country_code,
+            icon,
+        }
+    }
+}
+
 fn get_layout_code(layout_name: &str) -> String {
     let lower = layout_name.to_lowercase();
 
@@ -68,9 +118,9 @@ fn get_layout_code(layout_name: &str) -> String {
     }
 }
 
-fn code_to_flag(mut code: String) -> String {
-    // Special mappings
-    code = match code.as_str() {
+// Language/xkb code to the country whose flag represents it
+fn code_to_country(code: &str) -> String {
+    let country = match code {
         "uk" => "ua".to_string(),
         "en" => "us".to_string(),
         "ar" => "sa".to_string(),
@@ -102,11 +152,21 @@ fn code_to_flag(mut code: String) -> String {
         "km" => "kh".to_string(),
         "lo" => "la".to_string(),
         "ne" => "np".to_string(),
-        _ => code,
+        _ => code.to_string(),
     };
 
-    if code.len() == 2 {
-        let upper = code.to_uppercase();
+    if country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()) {
+        country.to_lowercase()
+    } else {
+        String::new()
+    }
+}
+
+fn code_to_flag(code: String) -> String {
+    let country = code_to_country(&code);
+
+    if !country.is_empty() {
+        let upper = country.to_uppercase();
         let chars: Vec<char> = upper.chars().collect();
 
         let c1 = chars[0] as u32 - 'A' as u32 + 0x1F1E6;
@@ -123,51 +183,109 @@ fn code_to_flag(mut code: String) -> String {
 }
 
 fn get_current_layout() -> KeyboardLayout {
-    match Connection::new() {
-        Ok(mut conn) => {
-            if let Ok(inputs) = conn.get_inputs() {
-                for input in inputs {
-                    if input.input_type == "keyboard" {
-                        if let Some(layout_name) = input.xkb_active_layout_name {
-                            let code = get_layout_code(&layout_name);
-                            let icon = code_to_flag(code.clone());
-                            return KeyboardLayout { layout: code, icon };
-                        }
-                    }
-                }
+    if let Ok(mut conn) = Connection::new() {
+        if let Ok(inputs) = conn.get_inputs() {
+            if let Some(layout) = inputs.into_iter().find_map(keyboard_layout) {
+                return layout;
             }
         }
-        Err(_) => {}
     }
 
-    KeyboardLayout {
-        layout: "us".to_string(),
-        icon: code_to_flag("us".to_string()),
+    KeyboardLayout::from_code("us".to_string())
+}
+
+// The active layout of a keyboard input; None for other devices
+fn keyboard_layout(input: Input) -> Option<KeyboardLayout> {
+    if input.input_type != "keyboard" {
+        return None;
     }
+    let layout_name = input.xkb_active_layout_name?;
+    Some(KeyboardLayout::from_code(get_layout_code(&layout_name)))
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+fn print_layout(layout: &KeyboardLayout, format: Format) {
+    match format {
+        Format::Json => println!("{}", serde_json::to_string(layout).unwrap()),
+        Format::Code => println!("{}", layout.layout),
+    }
+}
 
-    if args.len() > 1 && args[1] == "listen" {
-        // Listen mode: output current layout and monitor for changes
-        let layout = get_current_layout();
-        println!("{}", serde_json::to_string(&layout).unwrap());
+// Calls `on_change` with the current layout and again with the layout carried
+// by every keyboard input event, re-subscribing whenever sway restarts and the
+// stream ends
+fn watch_layout(mut on_change: impl FnMut(KeyboardLayout)) -> ! {
+    loop {
+        on_change(get_current_layout());
 
-        // Subscribe to input events
         if let Ok(conn) = Connection::new() {
-            if let Ok(events) = conn.subscribe(&[EventType::Input]) {
+            if let Ok(events) = conn.subscribe([EventType::Input]) {
                 for event in events {
-                    if let Ok(Event::Input(_)) = event {
-                        let layout = get_current_layout();
-                        println!("{}", serde_json::to_string(&layout).unwrap());
+                    match event {
+                        Ok(Event::Input(event)) => {
+                            if let Some(layout) = keyboard_layout(event.input) {
+                                on_change(layout);
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            eprintln!("Error: {}", e);
+                            break;
+                        }
                     }
                 }
             }
         }
-    } else {
-        // Default: just get current layout
-        let layout = get_current_layout();
-        println!("{}", serde_json::to_string(&layout).unwrap());
+
+        thread::sleep(Duration::from_millis(RECONNECT_DELAY_MS));
+    }
+}
+
+fn run_daemon(socket_path: &str) -> std::io::Result<()> {
+    if std::path::Path::new(socket_path).exists() {
+        let _ = std::fs::remove_file(socket_path);
+    }
+
+    let cached = Arc::new(Mutex::new(get_current_layout()));
+    let cached_watch = cached.clone();
+    thread::spawn(move || {
+        watch_layout(|layout| *cached_watch.lock().unwrap() = layout);
+    });
+
+    // Each connection gets the cached layout as one JSON line
+    let listener = UnixListener::bind(socket_path)?;
+    for mut stream in listener.incoming().flatten() {
+        let layout = cached.lock().unwrap().clone();
+        if let Ok(json) = serde_json::to_string(&layout) {
+            let _ = stream.write_all(json.as_bytes());
+            let _ = stream.write_all(b"\n");
+        }
+    }
+
+    Ok(())
+}
+
+fn query_daemon(socket_path: &str) -> Option<KeyboardLayout> {
+    let stream = UnixStream::connect(socket_path).ok()?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).ok()?;
+    serde_json::from_str(&line).ok()
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Some(Command::Listen) => watch_layout(|layout| print_layout(&layout, cli.format)),
+        Some(Command::Daemon) => {
+            if let Err(e) = run_daemon(&cli.socket) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        None => {
+            // Default: ask the daemon, falling back to querying sway directly
+            let layout = query_daemon(&cli.socket).unwrap_or_else(get_current_layout);
+            print_layout(&layout, cli.format);
+        }
     }
 }
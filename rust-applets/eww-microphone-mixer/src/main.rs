@@ -1,16 +1,18 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use libpulse_binding as pulse;
 use libpulse_binding::callbacks::ListResult;
 use libpulse_binding::context::{Context, FlagSet as ContextFlagSet};
+use libpulse_binding::def::{PortAvailable, SourceState};
 use libpulse_binding::mainloop::threaded::Mainloop;
 use libpulse_binding::proplist::Proplist;
 use libpulse_binding::volume::Volume;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
 use std::io::{BufRead, BufReader, Write};
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::rc::Rc;
-use std::sync::mpsc;
+use std::sync::{Mutex, mpsc};
 use std::thread;
 use std::time::Duration;
 
@@ -28,21 +30,109 @@ struct Cli {
 
 #[derive(Subcommand, Serialize, Deserialize, Debug, Clone, PartialEq)]
 enum CliCommand {
-    Listen,
+    Listen {
+        /// Percentage points VolumeUp/VolumeDown move the default source by
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u8).range(1..=100))]
+        step: u8,
+        /// Ceiling for VolumeUp and SetSourceVolume
+        #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u8).range(1..=150))]
+        max_volume: u8,
+        /// Show a notification when ToggleMuteNotify changes the mic mute
+        #[arg(long)]
+        notify: bool,
+        /// Percent scale for reported volumes and incoming SetSource*Volume values
+        #[arg(long, value_enum, default_value_t = ReportScale::Absolute)]
+        report_scale: ReportScale,
+    },
     GetState,
-    SetSourceVolume { source_index: u32, volume: u8 },
-    SetSourceOutputVolume { index: u32, volume: u8 },
-    SetDefaultSource { source_name: String },
-    MuteSource { source_index: u32, mute: bool },
-    ToggleMuteSource { source_index: u32 },
+    /// Mute and volume of the default source only
+    MicStatus,
+    SetSourceVolume {
+        source_index: u32,
+        volume: u8,
+    },
+    SetSourceOutputVolume {
+        index: u32,
+        volume: u8,
+    },
+    SetDefaultSource {
+        source_name: String,
+    },
+    CycleDefaultSource {
+        #[arg(value_enum, default_value_t = CycleDirection::Next)]
+        direction: CycleDirection,
+    },
+    MuteSource {
+        source_index: u32,
+        mute: bool,
+    },
+    ToggleMuteSource {
+        source_index: u32,
+    },
     ToggleMuteDefault,
+    /// ToggleMuteDefault plus an OSD notification (with `listen --notify`)
+    ToggleMuteNotify,
     VolumeUp,
     VolumeDown,
-    MuteSourceOutput { index: u32, mute: bool },
-    ToggleMuteSourceOutput { index: u32 },
+    MuteSourceOutput {
+        index: u32,
+        mute: bool,
+    },
+    ToggleMuteSourceOutput {
+        index: u32,
+    },
     Kill,
 }
 
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+enum ReportScale {
+    /// PulseAudio's own percent, 100 being unamplified
+    Absolute,
+    /// Percent of --max-volume, so the ceiling reads as 100
+    Normalized,
+}
+
+/// Converts between PulseAudio volumes and the percents clients see and send
+#[derive(Clone, Copy)]
+struct PercentScale {
+    report: ReportScale,
+    max_volume: u8,
+}
+
+impl PercentScale {
+    fn percent(self, volume: Volume) -> u8 {
+        let percent = volume.0 as f64 / Volume::NORMAL.0 as f64 * 100.0;
+        match self.report {
+            ReportScale::Absolute => percent as u8,
+            ReportScale::Normalized => (percent * 100.0 / self.max_volume as f64)
+                .round()
+                .min(255.0) as u8,
+        }
+    }
+
+    fn volume(self, percent: u8) -> Volume {
+        let percent = match self.report {
+            ReportScale::Absolute => percent as f64,
+            ReportScale::Normalized => percent as f64 * self.max_volume as f64 / 100.0,
+        };
+        Volume((Volume::NORMAL.0 as f64 * (percent.min(150.0) / 100.0)) as u32)
+    }
+
+    /// Highest percent VolumeUp and SetSourceVolume may set, in reported units
+    fn ceiling(self) -> u8 {
+        match self.report {
+            ReportScale::Absolute => self.max_volume,
+            ReportScale::Normalized => 100,
+        }
+    }
+}
+
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+enum CycleDirection {
+    Next,
+    Prev,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 struct SourceInfo {
     index: u32,
@@ -60,10 +150,12 @@ struct SourceOutputInfo {
     volume: u8,
     muted: bool,
     source_index: u32,
+    source_name: String, // Description of the source it records from
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 struct MicMixerState {
+    audio_available: bool, // False while PulseAudio is unreachable
     percent: u8,
     muted: bool,
     level: u8,
@@ -71,11 +163,19 @@ struct MicMixerState {
     source_outputs: Vec<SourceOutputInfo>,
 }
 
+/// Lightweight default-source status for mute indicators
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+struct MicStatus {
+    muted: bool,
+    volume: u8,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 enum DaemonResponse {
     Success,
     Error(String),
     State(MicMixerState),
+    MicStatus(MicStatus),
 }
 
 enum ActorMessage {
@@ -83,15 +183,58 @@ enum ActorMessage {
     Refresh,
 }
 
+/// Print a warning the first time this exact message comes up
+fn warn_once(message: String) {
+    static WARNED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+    if let Ok(mut warned) = WARNED.lock()
+        && warned.insert(message.clone())
+    {
+        eprintln!("Warning: {}", message);
+    }
+}
+
+/// Flag at most one source as default: the first whose name matches.
+/// Applied once after enumeration, using the name from a single server-info query.
+/// When no name matches (e.g. PipeWire renamed the node), fall back to the
+/// description the default had in the previous snapshot.
+fn mark_default(
+    sources: &mut [SourceInfo],
+    default_name: Option<&str>,
+    last_description: Option<&str>,
+) {
+    let position = sources
+        .iter()
+        .position(|source| Some(source.name.as_str()) == default_name)
+        .or_else(|| {
+            let description = last_description?;
+            let position = sources
+                .iter()
+                .position(|source| source.description == description)?;
+            warn_once(format!(
+                "default source '{}' not found, matched '{}' by description",
+                default_name.unwrap_or_default(),
+                sources[position].name
+            ));
+            Some(position)
+        });
+
+    for (i, source) in sources.iter_mut().enumerate() {
+        source.is_default = Some(i) == position;
+    }
+}
+
 // --- PULSEAUDIO ACTOR ---
 
 struct PulseAudioActor {
     mainloop: Rc<RefCell<Mainloop>>,
     context: Rc<RefCell<Context>>,
+    // Description of the last source flagged default, for rename-tolerant matching
+    default_description: RefCell<Option<String>>,
+    scale: PercentScale,
 }
 
 impl PulseAudioActor {
-    fn new() -> Result<Self, String> {
+    fn new(scale: PercentScale) -> Result<Self, String> {
         let mut proplist = Proplist::new().unwrap();
         proplist
             .set_str(
@@ -138,36 +281,41 @@ impl PulseAudioActor {
             }
         }
 
-        Ok(Self { mainloop, context })
+        Ok(Self {
+            mainloop,
+            context,
+            default_description: RefCell::new(None),
+            scale,
+        })
     }
 
     fn get_state(&self) -> MicMixerState {
-        let mut state = MicMixerState::default();
+        let mut state = MicMixerState {
+            audio_available: true,
+            ..Default::default()
+        };
+        let scale = self.scale;
 
         self.mainloop.borrow_mut().lock();
 
         // 1. Get Default Source
-        let mut introspect = self.context.borrow().introspect();
+        let introspect = self.context.borrow().introspect();
         let (tx, rx) = mpsc::channel();
         introspect.get_server_info(move |info| {
-            let name = info
-                .default_source_name
-                .as_ref()
-                .map(|n| n.to_string())
-                .unwrap_or_default();
+            let name = info.default_source_name.as_ref().map(|n| n.to_string());
             let _ = tx.send(name);
         });
 
         self.mainloop.borrow_mut().unlock();
-        let default_source_name = rx.recv().unwrap_or_default();
+        let default_source_name = rx.recv().ok().flatten();
         self.mainloop.borrow_mut().lock();
 
         // 2. Get Sources
-        let mut introspect = self.context.borrow().introspect();
+        let introspect = self.context.borrow().introspect();
         let (tx, rx) = mpsc::channel();
         introspect.get_source_info_list(move |res| match res {
             ListResult::Item(item) => {
-                let vol = (item.volume.avg().0 as f64 / Volume::NORMAL.0 as f64 * 100.0) as u8;
+                let vol = scale.percent(item.volume.avg());
                 let _ = tx.send(Some(SourceInfo {
                     index: item.index,
                     name: item
@@ -192,18 +340,17 @@ impl PulseAudioActor {
         });
 
         self.mainloop.borrow_mut().unlock();
-        while let Ok(Some(mut source)) = rx.recv() {
-            source.is_default = source.name == default_source_name;
+        while let Ok(Some(source)) = rx.recv() {
             state.sources.push(source);
         }
         self.mainloop.borrow_mut().lock();
 
         // 3. Get Source Outputs
-        let mut introspect = self.context.borrow().introspect();
+        let introspect = self.context.borrow().introspect();
         let (tx, rx) = mpsc::channel();
         introspect.get_source_output_info_list(move |res| match res {
             ListResult::Item(item) => {
-                let vol = (item.volume.avg().0 as f64 / Volume::NORMAL.0 as f64 * 100.0) as u8;
+                let vol = scale.percent(item.volume.avg());
                 let name = item
                     .proplist
                     .get_str(pulse::proplist::properties::APPLICATION_NAME)
@@ -214,6 +361,7 @@ impl PulseAudioActor {
                     volume: vol,
                     muted: item.mute,
                     source_index: item.source,
+                    source_name: String::new(),
                 }));
             }
             ListResult::End => {
@@ -223,14 +371,33 @@ impl PulseAudioActor {
         });
 
         self.mainloop.borrow_mut().unlock();
-        while let Ok(Some(output)) = rx.recv() {
+        let source_names: HashMap<u32, &str> = state
+            .sources
+            .iter()
+            .map(|s| (s.index, s.description.as_str()))
+            .collect();
+        while let Ok(Some(mut output)) = rx.recv() {
+            if let Some(name) = source_names.get(&output.source_index) {
+                output.source_name = name.to_string();
+            }
             state.source_outputs.push(output);
         }
 
         // Finalize
+        let last_description = self.default_description.borrow().clone();
+        mark_default(
+            &mut state.sources,
+            default_source_name.as_deref(),
+            last_description.as_deref(),
+        );
+        *self.default_description.borrow_mut() = state
+            .sources
+            .iter()
+            .find(|source| source.is_default)
+            .map(|source| source.description.clone());
         state
             .sources
-            .sort_by(|a, b| b.is_default.cmp(&a.is_default));
+            .sort_by_key(|s| std::cmp::Reverse(s.is_default));
         if let Some(def) = state.sources.iter().find(|s| s.is_default) {
             state.percent = def.volume;
             state.muted = def.muted;
@@ -245,7 +412,7 @@ impl PulseAudioActor {
     // Actions
     fn set_source_volume(&self, index: u32, percent: u8) {
         self.mainloop.borrow_mut().lock();
-        let mut introspect = self.context.borrow().introspect();
+        let introspect = self.context.borrow().introspect();
         let (tx, rx) = mpsc::channel();
 
         introspect.get_source_info_by_index(index, move |res| {
@@ -260,8 +427,7 @@ impl PulseAudioActor {
         if let Ok(Some(mut cv)) = rx.recv() {
             self.mainloop.borrow_mut().lock();
             let mut introspect = self.context.borrow().introspect();
-            let v_val = (Volume::NORMAL.0 as f64 * (percent.min(150) as f64 / 100.0)) as u32;
-            cv.scale(Volume(v_val));
+            cv.scale(self.scale.volume(percent));
             introspect.set_source_volume_by_index(index, &cv, None);
             self.mainloop.borrow_mut().unlock();
         }
@@ -269,7 +435,7 @@ impl PulseAudioActor {
 
     fn set_output_volume(&self, index: u32, percent: u8) {
         self.mainloop.borrow_mut().lock();
-        let mut introspect = self.context.borrow().introspect();
+        let introspect = self.context.borrow().introspect();
         let (tx, rx) = mpsc::channel();
 
         introspect.get_source_output_info(index, move |res| {
@@ -284,8 +450,7 @@ impl PulseAudioActor {
         if let Ok(Some(mut cv)) = rx.recv() {
             self.mainloop.borrow_mut().lock();
             let mut introspect = self.context.borrow().introspect();
-            let v_val = (Volume::NORMAL.0 as f64 * (percent.min(150) as f64 / 100.0)) as u32;
-            cv.scale(Volume(v_val));
+            cv.scale(self.scale.volume(percent));
             introspect.set_source_output_volume(index, &cv, None);
             self.mainloop.borrow_mut().unlock();
         }
@@ -307,7 +472,7 @@ impl PulseAudioActor {
 
     fn toggle_source_mute(&self, index: u32) {
         self.mainloop.borrow_mut().lock();
-        let mut introspect = self.context.borrow().introspect();
+        let introspect = self.context.borrow().introspect();
         let (tx, rx) = mpsc::channel();
 
         introspect.get_source_info_by_index(index, move |res| {
@@ -329,7 +494,7 @@ impl PulseAudioActor {
 
     fn toggle_output_mute(&self, index: u32) {
         self.mainloop.borrow_mut().lock();
-        let mut introspect = self.context.borrow().introspect();
+        let introspect = self.context.borrow().introspect();
         let (tx, rx) = mpsc::channel();
 
         introspect.get_source_output_info(index, move |res| {
@@ -349,16 +514,151 @@ impl PulseAudioActor {
         }
     }
 
+    /// Default source status from a single introspect query
+    fn get_mic_status(&self) -> Option<MicStatus> {
+        let scale = self.scale;
+        self.mainloop.borrow_mut().lock();
+        let introspect = self.context.borrow().introspect();
+        let (tx, rx) = mpsc::channel();
+        introspect.get_source_info_by_name("@DEFAULT_SOURCE@", move |res| match res {
+            ListResult::Item(item) => {
+                let vol = scale.percent(item.volume.avg());
+                let _ = tx.send(Some(MicStatus {
+                    muted: item.mute,
+                    volume: vol,
+                }));
+            }
+            _ => {
+                let _ = tx.send(None);
+            }
+        });
+        self.mainloop.borrow_mut().unlock();
+        rx.recv().ok().flatten()
+    }
+
     fn set_default_source(&self, name: &str) {
         self.mainloop.borrow_mut().lock();
         self.context.borrow_mut().set_default_source(name, |_| {});
         self.mainloop.borrow_mut().unlock();
     }
+
+    /// Names of sources worth cycling through: no monitors, nothing unplugged.
+    /// Idle-suspended sources are kept, since PulseAudio suspends every unused mic.
+    fn cyclable_source_names(&self) -> Vec<String> {
+        self.mainloop.borrow_mut().lock();
+        let introspect = self.context.borrow().introspect();
+        let (tx, rx) = mpsc::channel();
+        introspect.get_source_info_list(move |res| match res {
+            ListResult::Item(item) => {
+                let unplugged = item
+                    .active_port
+                    .as_ref()
+                    .is_some_and(|port| port.available == PortAvailable::No);
+                let usable = item.monitor_of_sink.is_none()
+                    && item.state != SourceState::Invalid
+                    && !unplugged;
+                if let (true, Some(name)) = (usable, item.name.as_ref()) {
+                    let _ = tx.send(Some(name.to_string()));
+                }
+            }
+            ListResult::End => {
+                let _ = tx.send(None);
+            }
+            _ => {}
+        });
+        self.mainloop.borrow_mut().unlock();
+
+        let mut names = Vec::new();
+        while let Ok(Some(name)) = rx.recv() {
+            names.push(name);
+        }
+        names
+    }
+
+    fn cycle_default_source(&self, direction: CycleDirection) -> Result<(), String> {
+        let names = self.cyclable_source_names();
+        if names.is_empty() {
+            return Err("No sources available to cycle".into());
+        }
+
+        let state = self.get_state();
+        let current = state
+            .sources
+            .iter()
+            .find(|s| s.is_default)
+            .and_then(|def| names.iter().position(|n| *n == def.name));
+
+        let len = names.len();
+        let next = match (current, direction) {
+            (Some(i), CycleDirection::Next) => (i + 1) % len,
+            (Some(i), CycleDirection::Prev) => (i + len - 1) % len,
+            (None, CycleDirection::Next) => 0,
+            (None, CycleDirection::Prev) => len - 1,
+        };
+
+        if Some(next) != current {
+            self.set_default_source(&names[next]);
+        }
+        Ok(())
+    }
 }
 
 // --- SERVER LOGIC ---
 
-fn run_server(socket_path: &str) -> anyhow::Result<()> {
+/// Serve the "no audio server" state, retrying the connection on each refresh tick.
+/// Returns the actor once PulseAudio is reachable again.
+fn handle_without_audio(msg: ActorMessage, scale: PercentScale) -> Option<PulseAudioActor> {
+    match msg {
+        ActorMessage::Refresh => {
+            let actor = PulseAudioActor::new(scale).ok()?;
+            eprintln!("PulseAudio is back, resuming normal operation");
+            if let Ok(json) = serde_json::to_string(&actor.get_state()) {
+                println!("{}", json);
+            }
+            Some(actor)
+        }
+        ActorMessage::Command(CliCommand::GetState, reply_tx) => {
+            let _ = reply_tx.send(DaemonResponse::State(MicMixerState::default()));
+            None
+        }
+        ActorMessage::Command(CliCommand::Kill, _) => std::process::exit(0),
+        ActorMessage::Command(_, reply_tx) => {
+            let _ = reply_tx.send(DaemonResponse::Error("No audio server available".into()));
+            None
+        }
+    }
+}
+
+/// Daemon settings from the `listen` flags
+#[derive(Clone, Copy)]
+struct DaemonOptions {
+    step: u8,
+    scale: PercentScale,
+    notify: bool,
+}
+
+/// Mic mute OSD; the synchronous hint makes each one replace the last
+fn notify_mute(muted: bool) {
+    let body = if muted { "Mic muted" } else { "Mic unmuted" };
+    let icon = if muted {
+        "microphone-sensitivity-muted"
+    } else {
+        "microphone-sensitivity-high"
+    };
+    let _ = std::process::Command::new("notify-send")
+        .args([
+            "-h",
+            "string:x-canonical-private-synchronous:eww-microphone-mixer",
+            "-i",
+            icon,
+            "-u",
+            "low",
+            body,
+        ])
+        .spawn();
+}
+
+fn run_server(socket_path: &str, options: DaemonOptions) -> anyhow::Result<()> {
     if std::path::Path::new(socket_path).exists() {
         let _ = std::fs::remove_file(socket_path);
     }
@@ -368,20 +668,24 @@ fn run_server(socket_path: &str) -> anyhow::Result<()> {
 
     // 1. ACTOR THREAD
     thread::spawn(move || {
-        let actor = match PulseAudioActor::new() {
-            Ok(a) => a,
+        let mut actor = match PulseAudioActor::new(options.scale) {
+            Ok(a) => Some(a),
             Err(e) => {
-                eprintln!("Failed to init PulseAudio: {}", e);
-                return;
+                eprintln!("PulseAudio unavailable ({}), retrying in background", e);
+                None
             }
         };
 
-        let init = actor.get_state();
+        let init = actor.as_ref().map(|a| a.get_state()).unwrap_or_default();
         if let Ok(json) = serde_json::to_string(&init) {
             println!("{}", json);
         }
 
         while let Ok(msg) = receiver.recv() {
+            let Some(actor) = actor.as_ref() else {
+                actor = handle_without_audio(msg, options.scale);
+                continue;
+            };
             match msg {
                 ActorMessage::Refresh => {
                     let state = actor.get_state();
@@ -398,11 +702,20 @@ fn run_server(socket_path: &str) -> anyhow::Result<()> {
                             continue;
                         }
                         CliCommand::Kill => std::process::exit(0),
+                        CliCommand::MicStatus => {
+                            let response = match actor.get_mic_status() {
+                                Some(status) => DaemonResponse::MicStatus(status),
+                                None => DaemonResponse::Error("No default source".into()),
+                            };
+                            let _ = reply_tx.send(response);
+                            continue;
+                        }
 
                         CliCommand::SetSourceVolume {
                             source_index,
                             volume,
-                        } => actor.set_source_volume(source_index, volume),
+                        } => actor
+                            .set_source_volume(source_index, volume.min(options.scale.ceiling())),
                         CliCommand::SetSourceOutputVolume { index, volume } => {
                             actor.set_output_volume(index, volume)
                         }
@@ -421,6 +734,12 @@ fn run_server(socket_path: &str) -> anyhow::Result<()> {
                         CliCommand::SetDefaultSource { source_name } => {
                             actor.set_default_source(&source_name)
                         }
+                        CliCommand::CycleDefaultSource { direction } => {
+                            if let Err(e) = actor.cycle_default_source(direction) {
+                                let _ = reply_tx.send(DaemonResponse::Error(e));
+                                continue;
+                            }
+                        }
 
                         CliCommand::ToggleMuteDefault => {
                             let s = actor.get_state();
@@ -428,16 +747,30 @@ fn run_server(socket_path: &str) -> anyhow::Result<()> {
                                 actor.toggle_source_mute(def.index);
                             }
                         }
+                        CliCommand::ToggleMuteNotify => {
+                            let s = actor.get_state();
+                            if let Some(def) = s.sources.iter().find(|x| x.is_default) {
+                                actor.toggle_source_mute(def.index);
+                                if options.notify {
+                                    notify_mute(!def.muted);
+                                }
+                            }
+                        }
                         CliCommand::VolumeUp => {
                             let s = actor.get_state();
                             if let Some(def) = s.sources.iter().find(|x| x.is_default) {
-                                actor.set_source_volume(def.index, (def.volume + 5).min(100));
+                                let volume = def.volume.saturating_add(options.step);
+                                actor.set_source_volume(
+                                    def.index,
+                                    volume.min(options.scale.ceiling()),
+                                );
                             }
                         }
                         CliCommand::VolumeDown => {
                             let s = actor.get_state();
                             if let Some(def) = s.sources.iter().find(|x| x.is_default) {
-                                actor.set_source_volume(def.index, def.volume.saturating_sub(5));
+                                let volume = def.volume.saturating_sub(options.step);
+                                actor.set_source_volume(def.index, volume);
                             }
                         }
                         _ => {
@@ -470,13 +803,11 @@ fn run_server(socket_path: &str) -> anyhow::Result<()> {
 
     // 3. LISTENER THREAD
     let listener = UnixListener::bind(socket_path)?;
-    for stream in listener.incoming() {
-        if let Ok(stream) = stream {
-            let sender = sender_sock.clone();
-            thread::spawn(move || {
-                handle_client(stream, sender);
-            });
-        }
+    for stream in listener.incoming().flatten() {
+        let sender = sender_sock.clone();
+        thread::spawn(move || {
+            handle_client(stream, sender);
+        });
     }
 
     Ok(())
@@ -486,17 +817,17 @@ fn handle_client(stream: UnixStream, sender: mpsc::Sender<ActorMessage>) {
     let mut reader = BufReader::new(stream);
     let mut line = String::new();
 
-    if let Ok(_) = reader.read_line(&mut line) {
-        if let Ok(cmd) = serde_json::from_str::<CliCommand>(line.trim()) {
-            let (tx, rx) = mpsc::channel();
-            if sender.send(ActorMessage::Command(cmd, tx)).is_ok() {
-                if let Ok(response) = rx.recv() {
-                    let mut stream = reader.into_inner();
-                    if let Ok(json) = serde_json::to_string(&response) {
-                        let _ = stream.write_all(json.as_bytes());
-                        let _ = stream.write_all(b"\n");
-                    }
-                }
+    if reader.read_line(&mut line).is_ok()
+        && let Ok(cmd) = serde_json::from_str::<CliCommand>(line.trim())
+    {
+        let (tx, rx) = mpsc::channel();
+        if sender.send(ActorMessage::Command(cmd, tx)).is_ok()
+            && let Ok(response) = rx.recv()
+        {
+            let mut stream = reader.into_inner();
+            if let Ok(json) = serde_json::to_string(&response) {
+                let _ = stream.write_all(json.as_bytes());
+                let _ = stream.write_all(b"\n");
             }
         }
     }
@@ -522,6 +853,10 @@ fn send_command(socket_path: &str, cmd: CliCommand) -> anyhow::Result<()> {
             println!("{}", serde_json::to_string(&s)?);
             Ok(())
         }
+        DaemonResponse::MicStatus(s) => {
+            println!("{}", serde_json::to_string(&s)?);
+            Ok(())
+        }
         DaemonResponse::Error(e) => Err(anyhow::anyhow!("Daemon error: {}", e)),
     }
 }
@@ -529,7 +864,72 @@ fn send_command(socket_path: &str, cmd: CliCommand) -> anyhow::Result<()> {
 fn main() -> anyhow::Result<()> {
     let args = Cli::parse();
     match args.command {
-        CliCommand::Listen => run_server(&args.socket),
+        CliCommand::Listen {
+            step,
+            max_volume,
+            notify,
+            report_scale,
+        } => run_server(
+            &args.socket,
+            DaemonOptions {
+                step,
+                scale: PercentScale {
+                    report: report_scale,
+                    max_volume,
+                },
+                notify,
+            },
+        ),
         cmd => send_command(&args.socket, cmd),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(index: u32, name: &str, description: &str) -> SourceInfo {
+        SourceInfo {
+            index,
+            name: name.to_string(),
+            description: description.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn default_source_listed_second_is_the_only_one_flagged() {
+        // Mock backend listing: the default source is enumerated second
+        let mut sources = vec![
+            source(1, "alsa_input.webcam", "Webcam"),
+            source(2, "alsa_input.usb", "USB Mic"),
+            source(3, "bluez_input.headset", "Headset"),
+        ];
+
+        mark_default(&mut sources, Some("alsa_input.usb"), None);
+
+        let flagged: Vec<u32> = sources
+            .iter()
+            .filter(|s| s.is_default)
+            .map(|s| s.index)
+            .collect();
+        assert_eq!(flagged, [2]);
+    }
+
+    #[test]
+    fn renamed_default_source_is_matched_by_description() {
+        let mut sources = vec![
+            source(1, "alsa_input.webcam", "Webcam"),
+            source(2, "alsa_input.usb.2", "USB Mic"),
+        ];
+
+        mark_default(&mut sources, Some("alsa_input.usb"), Some("USB Mic"));
+
+        let flagged: Vec<u32> = sources
+            .iter()
+            .filter(|s| s.is_default)
+            .map(|s| s.index)
+            .collect();
+        assert_eq!(flagged, [2]);
+    }
+}
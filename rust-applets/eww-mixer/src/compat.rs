@@ -0,0 +1,160 @@
+// ============================================================================
+// COMPAT OUTPUT (--compat)
+// ============================================================================
+//
+// Re-shapes MixerState into the JSON printed by eww-volume-mixer and
+// eww-microphone-mixer, so widgets written for them can be pointed at this
+// daemon before they are migrated.
+//
+// eww-volume-mixer (`--compat volume`):
+// - audio_available <- audio_available
+// - percent / muted <- volume_percent / volume_muted
+// - level           <- volume_level (always 0 in the old mixer)
+// - sinks           <- sinks without channel_volumes / channel_labels / balance
+// - sink_inputs     <- sink_inputs without balance
+//
+// eww-microphone-mixer (`--compat microphone`):
+// - audio_available <- audio_available
+// - percent / muted <- mic_percent / mic_muted
+// - level           <- mic_level (always 0 in the old mixer)
+// - sources         <- sources without channel_volumes / channel_labels
+// - source_outputs  <- source_outputs, plus source_name: the description of
+//                      the source at source_index
+//
+// ============================================================================
+
+use crate::MixerState;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Compat {
+    /// eww-volume-mixer's state
+    Volume,
+    /// eww-microphone-mixer's state
+    Microphone,
+}
+
+#[derive(Serialize)]
+struct DeviceInfo<'a> {
+    index: u32,
+    name: &'a str,
+    description: &'a str,
+    volume: u8,
+    muted: bool,
+    is_default: bool,
+}
+
+#[derive(Serialize)]
+struct SinkInputInfo<'a> {
+    index: u32,
+    name: &'a str,
+    volume: u8,
+    muted: bool,
+    sink_index: u32,
+}
+
+#[derive(Serialize)]
+struct VolumeMixerState<'a> {
+    audio_available: bool,
+    percent: u8,
+    muted: bool,
+    level: u8,
+    sinks: Vec<DeviceInfo<'a>>,
+    sink_inputs: Vec<SinkInputInfo<'a>>,
+}
+
+#[derive(Serialize)]
+struct SourceOutputInfo<'a> {
+    index: u32,
+    name: &'a str,
+    volume: u8,
+    muted: bool,
+    source_index: u32,
+    source_name: &'a str,
+}
+
+#[derive(Serialize)]
+struct MicMixerState<'a> {
+    audio_available: bool,
+    percent: u8,
+    muted: bool,
+    level: u8,
+    sources: Vec<DeviceInfo<'a>>,
+    source_outputs: Vec<SourceOutputInfo<'a>>,
+}
+
+/// State as one JSON line, in the requested older mixer's shape if any
+pub fn to_json(state: &MixerState, compat: Option<Compat>) -> serde_json::Result<String> {
+    match compat {
+        None => serde_json::to_string(state),
+        Some(Compat::Volume) => serde_json::to_string(&VolumeMixerState {
+            audio_available: state.audio_available,
+            percent: state.volume_percent,
+            muted: state.volume_muted,
+            level: state.volume_level,
+            sinks: state
+                .sinks
+                .iter()
+                .map(|s| DeviceInfo {
+                    index: s.index,
+                    name: &s.name,
+                    description: &s.description,
+                    volume: s.volume,
+                    muted: s.muted,
+                    is_default: s.is_default,
+                })
+                .collect(),
+            sink_inputs: state
+                .sink_inputs
+                .iter()
+                .map(|i| SinkInputInfo {
+                    index: i.index,
+                    name: &i.name,
+                    volume: i.volume,
+                    muted: i.muted,
+                    sink_index: i.sink_index,
+                })
+                .collect(),
+        }),
+        Some(Compat::Microphone) => {
+            let descriptions: HashMap<u32, &str> = state
+                .sources
+                .iter()
+                .map(|s| (s.index, s.description.as_str()))
+                .collect();
+
+            serde_json::to_string(&MicMixerState {
+                audio_available: state.audio_available,
+                percent: state.mic_percent,
+                muted: state.mic_muted,
+                level: state.mic_level,
+                sources: state
+                    .sources
+                    .iter()
+                    .map(|s| DeviceInfo {
+                        index: s.index,
+                        name: &s.name,
+                        description: &s.description,
+                        volume: s.volume,
+                        muted: s.muted,
+                        is_default: s.is_default,
+                    })
+                    .collect(),
+                source_outputs: state
+                    .source_outputs
+                    .iter()
+                    .map(|o| SourceOutputInfo {
+                        index: o.index,
+                        name: &o.name,
+                        volume: o.volume,
+                        muted: o.muted,
+                        source_index: o.source_index,
+                        source_name: descriptions.get(&o.source_index).copied().unwrap_or(""),
+                    })
+                    .collect(),
+            })
+        }
+    }
+}
@@ -9,7 +9,10 @@
 // - Complete device discovery (sinks, sources, inputs, outputs)
 // - Full volume and mute control for all audio targets
 // - Default device management
+// - Push-to-talk for the default source
 // - Unix socket command interface
+// - Optional PipeWire-native fallback (cargo feature "pipewire")
+// - Output in the older volume/microphone mixers' shapes (--compat)
 //
 // Architecture:
 // - Main async thread: Handles Unix socket connections (tokio)
@@ -20,32 +23,53 @@
 //
 // ============================================================================
 
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use libpulse_binding::{
     callbacks::ListResult,
-    context::{subscribe::Facility, Context, FlagSet as ContextFlagSet},
+    channelmap::{Map as ChannelMap, Position},
+    context::{introspect, subscribe::Facility, Context, FlagSet as ContextFlagSet},
+    def::{SinkState, SourceState},
     mainloop::threaded::Mainloop,
     proplist::Proplist,
     sample::{Format, Spec},
     stream::{FlagSet as StreamFlagSet, Stream},
-    volume::Volume,
+    volume::{ChannelVolumes, Volume},
 };
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::{
-    atomic::{AtomicU8, Ordering},
+    atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering},
     Arc,
 };
 
+mod compat;
+#[cfg(feature = "pipewire")]
+mod pipewire;
+
+use compat::Compat;
+
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::{UnixListener, UnixStream};
-use tokio::sync::broadcast;
-use tokio::time::{sleep, Duration};
 
 const DEFAULT_SOCKET_PATH: &str = "/tmp/eww-mixer.sock";
-const BROADCAST_CHANNEL_SIZE: usize = 100;
 const STATE_UPDATE_INTERVAL_MS: u64 = 50;
+const PEAK_DECAY_INTERVAL_MS: u64 = 50;
+const PEAK_DECAY_STEP: u64 = 3;
+const AUDIO_RETRY_INTERVAL_MS: u64 = 2000;
+// Covers PulseAudioActor::new's 5s connection timeout before the first state
+const INITIAL_STATE_TIMEOUT_MS: u64 = 7000;
+const PEEK_ERROR_LOG_INTERVAL_MS: u64 = 1000;
+const MONITOR_REBUILD_ERRORS: u32 = 50;
+// Peaks at or below this count as silence for --meter-idle-timeout (mic hiss)
+const METER_IDLE_PEAK: u8 = 3;
+// Index standing for the current default sink/source ("default" on the command line)
+const DEFAULT_INDEX: u32 = u32::MAX;
+// Client exit statuses; the mute ones are only used by GetState's flags
+const EXIT_SINK_MUTED: i32 = 1;
+const EXIT_SOURCE_MUTED: i32 = 2;
+const EXIT_DAEMON_ERROR: i32 = 3;
+const EXIT_NO_DAEMON: i32 = 4;
 
 // ============================================================================
 // CLI DEFINITIONS
@@ -64,23 +88,60 @@ struct Cli {
 #[derive(Subcommand, Serialize, Deserialize, Clone)]
 enum CliCommand {
     /// Start the mixer daemon and listen for commands
-    Listen,
+    Listen {
+        #[command(flatten)]
+        config: DaemonConfig,
+    },
 
-    /// Get current mixer state
-    GetState,
+    /// Get current mixer state. Like every client command, exits with status 3
+    /// when the daemon reports an error and 4 when it can't be reached.
+    GetState {
+        /// Exit with status 1 when the default sink is muted
+        #[arg(long)]
+        exit_on_mute: bool,
+        /// Exit with status 2 when the default source is muted
+        #[arg(long)]
+        check_mic: bool,
+        /// Print the state in an older mixer's shape (see src/compat.rs)
+        #[arg(long, value_enum)]
+        compat: Option<Compat>,
+    },
 
-    /// Set volume for a device or application
+    /// Set volume for a device or application. For sinks and sources the
+    /// index may be `default`, resolved when the daemon runs the command.
     SetVolume {
+        #[arg(value_enum)]
+        target: AudioTarget,
+        #[arg(value_parser = parse_index)]
+        index: u32,
+        volume: u8,
+    },
+
+    /// Set the volume of a single channel (0-based, see `channel_labels`)
+    SetChannelVolume {
         #[arg(value_enum)]
         target: AudioTarget,
         index: u32,
+        channel: u8,
         volume: u8,
     },
 
-    /// Toggle mute for a device or application
+    /// Pan a device or application between left (-100) and right (100); index
+    /// may be `default`, as for SetVolume. Mono targets stay centred.
+    SetBalance {
+        #[arg(value_enum)]
+        target: AudioTarget,
+        #[arg(value_parser = parse_index)]
+        index: u32,
+        #[arg(allow_hyphen_values = true, value_parser = clap::value_parser!(i8).range(-100..=100))]
+        balance: i8,
+    },
+
+    /// Toggle mute for a device or application (index may be `default`, as for SetVolume)
     ToggleMute {
         #[arg(value_enum)]
         target: AudioTarget,
+        #[arg(value_parser = parse_index)]
         index: u32,
     },
 
@@ -91,10 +152,104 @@ enum CliCommand {
         name: String,
     },
 
+    /// Push-to-talk: `true` unmutes the default source, `false` restores its prior mute
+    PushToTalk {
+        #[arg(action = clap::ArgAction::Set)]
+        press: bool,
+    },
+
+    /// Print one target's current info, or why it was not found. Streams hidden
+    /// by --ignore-app or --max-inputs are found too; index may be `default`,
+    /// as for SetVolume.
+    Describe {
+        #[arg(value_enum)]
+        target: AudioTarget,
+        #[arg(value_parser = parse_index)]
+        index: u32,
+    },
+
+    /// Print the full device topology once, without a running daemon
+    Dump,
+
     /// Kill the daemon
     Kill,
 }
 
+/// A numeric index, or `default` for DEFAULT_INDEX
+fn parse_index(arg: &str) -> Result<u32, String> {
+    if arg == "default" {
+        return Ok(DEFAULT_INDEX);
+    }
+    arg.parse()
+        .map_err(|_| format!("expected an index or \"default\", got {:?}", arg))
+}
+
+/// Daemon-side options that shape the state produced by the actor
+#[derive(Args, Serialize, Deserialize, Clone, Debug)]
+struct DaemonConfig {
+    /// Keep at most N sink inputs and N source outputs (newest streams win)
+    #[arg(long)]
+    max_inputs: Option<usize>,
+
+    /// Hide sink inputs whose application name contains this text (repeatable).
+    /// Device-level meters still cover the whole sink.
+    #[arg(long = "ignore-app")]
+    ignore_apps: Vec<String>,
+
+    /// Milliseconds between full device-state refreshes. Every refresh wakes the
+    /// actor and re-enumerates PulseAudio, so raise this on battery-powered machines.
+    #[arg(long, default_value_t = STATE_UPDATE_INTERVAL_MS)]
+    update_interval_ms: u64,
+
+    /// Milliseconds between peak-meter updates (defaults to --update-interval-ms).
+    /// Lower values give smoother VU meters at the cost of more wakeups.
+    #[arg(long)]
+    meter_interval_ms: Option<u64>,
+
+    /// Order of sinks/sources after the default, which is always listed first
+    #[arg(long, value_enum, default_value_t = DeviceSort::Default)]
+    sort: DeviceSort,
+
+    /// Meter this sink (by name) in volume_level instead of following the default.
+    /// The meter reads zero while the sink is missing and resumes when it returns.
+    #[arg(long)]
+    monitor_sink: Option<String>,
+
+    /// How slider percents map to backend volumes. Cubic gives finer control
+    /// at low levels; reported percents use the same curve so sliders round-trip.
+    #[arg(long, value_enum, default_value_t = VolumeCurve::Linear)]
+    volume_curve: VolumeCurve,
+
+    /// Disconnect the peak monitors after this many seconds without peaks,
+    /// client commands or socket clients connected, so the sound card can
+    /// suspend. They reconnect when a client connects or sends a command, or
+    /// another client plays to the sink or records.
+    #[arg(long)]
+    meter_idle_timeout: Option<u64>,
+
+    /// Print state lines in eww-volume-mixer's or eww-microphone-mixer's shape,
+    /// for widgets not yet migrated to the full state
+    #[arg(long, value_enum)]
+    compat: Option<Compat>,
+}
+
+impl Default for DaemonConfig {
+    /// No filtering or limits: every device and stream is reported
+    fn default() -> Self {
+        DaemonConfig {
+            max_inputs: None,
+            ignore_apps: Vec::new(),
+            update_interval_ms: STATE_UPDATE_INTERVAL_MS,
+            meter_interval_ms: None,
+            sort: DeviceSort::Default,
+            monitor_sink: None,
+            volume_curve: VolumeCurve::Linear,
+            meter_idle_timeout: None,
+            compat: None,
+        }
+    }
+}
+
 #[derive(ValueEnum, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 enum AudioTarget {
     Sink,
@@ -109,6 +264,52 @@ enum DefaultTarget {
     Source,
 }
 
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+enum DeviceSort {
+    /// Backend enumeration order
+    Default,
+    /// Description, case-insensitive
+    Name,
+    /// Device index
+    Index,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+enum VolumeCurve {
+    /// Backend volume is proportional to the percent
+    Linear,
+    /// Backend volume is percent^3
+    Cubic,
+}
+
+impl VolumeCurve {
+    /// Backend volume as a fraction of 100%
+    fn fraction(self, percent: u8) -> f64 {
+        let p = percent as f64 / 100.0;
+        match self {
+            VolumeCurve::Linear => p,
+            VolumeCurve::Cubic => p.powi(3),
+        }
+    }
+
+    /// Inverse of `fraction`, rounded so a percent that was set reads back unchanged
+    fn percent(self, fraction: f64) -> u8 {
+        let p = match self {
+            VolumeCurve::Linear => fraction,
+            VolumeCurve::Cubic => fraction.cbrt(),
+        };
+        (p * 100.0).round().min(255.0) as u8
+    }
+
+    fn to_volume(self, percent: u8) -> Volume {
+        Volume((Volume::NORMAL.0 as f64 * self.fraction(percent)).round() as u32)
+    }
+
+    fn volume_percent(self, volume: Volume) -> u8 {
+        self.percent(volume.0 as f64 / Volume::NORMAL.0 as f64)
+    }
+}
+
 // ============================================================================
 // DATA STRUCTURES
 // ============================================================================
@@ -122,6 +323,9 @@ struct SinkInfo {
     volume: u8,
     muted: bool,
     is_default: bool,
+    channel_volumes: Vec<u8>, // Per-channel volume (0-100), in channel map order
+    channel_labels: Vec<String>, // Channel positions, e.g. "front-left"
+    balance: i8,              // -100 (left) to 100 (right), 0 when centred or mono
 }
 
 /// Sink input (playing application) information
@@ -132,6 +336,8 @@ struct SinkInputInfo {
     volume: u8,
     muted: bool,
     sink_index: u32,
+    balance: i8,                 // -100 (left) to 100 (right), 0 when centred or mono
+    channel_labels: Vec<String>, // Channel positions, e.g. "front-left"
 }
 
 /// Source (input device) information
@@ -143,6 +349,8 @@ struct SourceInfo {
     volume: u8,
     muted: bool,
     is_default: bool,
+    channel_volumes: Vec<u8>, // Per-channel volume (0-100), in channel map order
+    channel_labels: Vec<String>, // Channel positions, e.g. "front-left"
 }
 
 /// Source output (recording application) information
@@ -153,23 +361,33 @@ struct SourceOutputInfo {
     volume: u8,
     muted: bool,
     source_index: u32,
+    channel_labels: Vec<String>, // Channel positions, e.g. "front-left"
 }
 
 /// Complete mixer state with all devices and applications
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 struct MixerState {
+    // False while no audio server is reachable (device lists are empty)
+    audio_available: bool,
     // Default device summary for quick widget access
     volume_percent: u8,
     volume_muted: bool,
     volume_level: u8, // Peak level for visualization (0-100) - now real-time
+    // Per-channel peaks of the default sink; volume_level is their max
+    volume_level_left: u8,
+    volume_level_right: u8,
     mic_percent: u8,
     mic_muted: bool,
     mic_level: u8, // Peak level for visualization (0-100) - now real-time
+    // False when no device is flagged default and the summary came from the first one
+    default_sink_resolved: bool,
+    default_source_resolved: bool,
     // Full device listings for detailed mixer windows
     sinks: Vec<SinkInfo>,
     sink_inputs: Vec<SinkInputInfo>,
     sources: Vec<SourceInfo>,
     source_outputs: Vec<SourceOutputInfo>,
+    inputs_truncated: u32, // Streams dropped by --max-inputs
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -177,6 +395,35 @@ enum DaemonResponse {
     Success,
     Error(String),
     State(MixerState),
+    Target(TargetInfo),
+}
+
+/// A socket request line: a bare command, or `{"id": .., "command": ..}` to have
+/// the reply wrapped as `{"id": .., "response": ..}`. Several requests may be
+/// sent over one connection; ids let pipelining clients match the replies.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Request {
+    Tagged {
+        id: serde_json::Value,
+        command: CliCommand,
+    },
+    Bare(CliCommand),
+}
+
+#[derive(Serialize)]
+struct Reply {
+    id: serde_json::Value,
+    response: DaemonResponse,
+}
+
+/// One target as reported by `Describe`, tagged with its type
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum TargetInfo {
+    Sink(SinkInfo),
+    SinkInput(SinkInputInfo),
+    Source(SourceInfo),
+    SourceOutput(SourceOutputInfo),
 }
 
 // ============================================================================
@@ -191,6 +438,19 @@ enum ActorCommand {
         percent: u8,
         response: std::sync::mpsc::Sender<Result<(), String>>,
     },
+    SetChannelVolume {
+        target: AudioTarget,
+        index: u32,
+        channel: u8,
+        percent: u8,
+        response: std::sync::mpsc::Sender<Result<(), String>>,
+    },
+    SetBalance {
+        target: AudioTarget,
+        index: u32,
+        balance: i8,
+        response: std::sync::mpsc::Sender<Result<(), String>>,
+    },
     ToggleMute {
         target: AudioTarget,
         index: u32,
@@ -201,7 +461,15 @@ enum ActorCommand {
         name: String,
         response: std::sync::mpsc::Sender<Result<(), String>>,
     },
-    Subscribe,
+    PushToTalk {
+        press: bool,
+        response: std::sync::mpsc::Sender<Result<(), String>>,
+    },
+    Describe {
+        target: AudioTarget,
+        index: u32,
+        response: std::sync::mpsc::Sender<Result<TargetInfo, String>>,
+    },
 }
 
 // ============================================================================
@@ -211,21 +479,91 @@ enum ActorCommand {
 struct PulseAudioActor {
     mainloop: Rc<RefCell<Mainloop>>,
     context: Rc<RefCell<Context>>,
+    config: DaemonConfig,
     last_state: MixerState,
     broadcast_tx: Option<std::sync::mpsc::Sender<MixerState>>,
 
-    // Sink monitoring
+    // Sink monitoring (stereo)
     monitor_stream: Option<Rc<RefCell<Stream>>>,
-    peak_level: Arc<AtomicU8>,
+    monitored_sink: Option<String>,
+    peak_left: Arc<AtomicU8>,
+    peak_right: Arc<AtomicU8>,
 
     // Source (Mic) monitoring
     mic_monitor_stream: Option<Rc<RefCell<Stream>>>, // NEW
+    mic_monitored_source: Option<String>,
     mic_peak_level: Arc<AtomicU8>,
+
+    // Set by a monitor's read callback once its peeks keep failing
+    monitor_failed: Arc<AtomicBool>,
+    mic_monitor_failed: Arc<AtomicBool>,
+    last_monitor_rebuild: std::time::Instant,
+
+    // Both monitors stay disconnected while suspended by --meter-idle-timeout
+    meters_suspended: bool,
+    last_meter_activity: std::time::Instant,
+    // Open socket connections; the meters never suspend while any is open
+    connected_clients: Arc<AtomicUsize>,
+    // Sinks and sources PulseAudio reported as running in the last get_state
+    running_devices: Vec<String>,
+
+    // Source index and mute state saved by a push-to-talk press
+    ptt_saved: Option<(u32, bool)>,
+}
+
+/// Peek error bookkeeping for one monitor stream's read callback: logs at most
+/// once per interval and flags the stream for a rebuild after repeated failures
+struct PeekErrorLog {
+    stream: &'static str,
+    last_logged: Option<std::time::Instant>,
+    suppressed: u32,
+    consecutive: u32,
+    failed: Arc<AtomicBool>,
+}
+
+impl PeekErrorLog {
+    fn new(stream: &'static str, failed: Arc<AtomicBool>) -> Self {
+        failed.store(false, Ordering::Relaxed);
+        Self {
+            stream,
+            last_logged: None,
+            suppressed: 0,
+            consecutive: 0,
+            failed,
+        }
+    }
+
+    fn error(&mut self, e: impl std::fmt::Debug) {
+        self.consecutive += 1;
+        if self.consecutive == MONITOR_REBUILD_ERRORS {
+            self.failed.store(true, Ordering::Relaxed);
+        }
+
+        let interval = std::time::Duration::from_millis(PEEK_ERROR_LOG_INTERVAL_MS);
+        if self.last_logged.is_some_and(|t| t.elapsed() < interval) {
+            self.suppressed += 1;
+            return;
+        }
+        if self.suppressed > 0 {
+            eprintln!(
+                "{} peek error: {:?} ({} more suppressed)",
+                self.stream, e, self.suppressed
+            );
+        } else {
+            eprintln!("{} peek error: {:?}", self.stream, e);
+        }
+        self.last_logged = Some(std::time::Instant::now());
+        self.suppressed = 0;
+    }
+
+    fn ok(&mut self) {
+        self.consecutive = 0;
+    }
 }
 
 impl PulseAudioActor {
     /// Create new PulseAudio connection
-    fn new() -> anyhow::Result<Self> {
+    fn new(config: DaemonConfig) -> anyhow::Result<Self> {
         let mut proplist = Proplist::new().unwrap();
         proplist
             .set_str(
@@ -269,43 +607,60 @@ impl PulseAudioActor {
         Ok(Self {
             mainloop: mainloop_rc,
             context: context_rc,
+            config,
             last_state: MixerState::default(),
             broadcast_tx: None,
             monitor_stream: None,
-            peak_level: Arc::new(AtomicU8::new(0)),
+            monitored_sink: None,
+            peak_left: Arc::new(AtomicU8::new(0)),
+            peak_right: Arc::new(AtomicU8::new(0)),
 
             mic_monitor_stream: None, // NEW
+            mic_monitored_source: None,
             mic_peak_level: Arc::new(AtomicU8::new(0)),
+            monitor_failed: Arc::new(AtomicBool::new(false)),
+            mic_monitor_failed: Arc::new(AtomicBool::new(false)),
+            last_monitor_rebuild: std::time::Instant::now(),
+            meters_suspended: false,
+            last_meter_activity: std::time::Instant::now(),
+            connected_clients: Arc::new(AtomicUsize::new(0)),
+            running_devices: Vec::new(),
+            ptt_saved: None,
         })
     }
 
-    /// Calculates the peak volume (0-100) from raw audio data (PCM).
-    fn calculate_peak_volume(data: &[u8]) -> u8 {
-        if data.len() < 2 {
-            return 0;
-        }
+    /// Calculates the peak volume (0-100) of each channel from interleaved
+    /// S16LE audio data (PCM).
+    fn calculate_peak_volume<const CHANNELS: usize>(data: &[u8]) -> [u8; CHANNELS] {
+        let mut sum_squares = [0.0f64; CHANNELS];
+        let mut frames = 0usize;
 
-        let samples: Vec<i16> = data
-            .chunks_exact(2)
-            .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
-            .collect();
+        for frame in data.chunks_exact(2 * CHANNELS) {
+            for (channel, sample) in frame.chunks_exact(2).enumerate() {
+                let s = i16::from_le_bytes([sample[0], sample[1]]) as f64;
+                sum_squares[channel] += s * s;
+            }
+            frames += 1;
+        }
 
-        if samples.is_empty() {
-            return 0;
+        if frames == 0 {
+            return [0; CHANNELS];
         }
 
         // Root mean square calculation (Adjusted reference for visual feedback)
-        let sum_squares: f64 = samples.iter().map(|&s| (s as f64 * s as f64)).sum();
-        let rms = (sum_squares / samples.len() as f64).sqrt();
-
-        let reference_level = 12000.0;
-        let normalized = (rms / reference_level).min(1.0);
-        let compressed = normalized.powf(0.7);
-        let result = (compressed * 115.0).min(100.0);
-        result.round() as u8
+        sum_squares.map(|sum| {
+            let rms = (sum / frames as f64).sqrt();
+
+            let reference_level = 12000.0;
+            let normalized = (rms / reference_level).min(1.0);
+            let compressed = normalized.powf(0.7);
+            let result = (compressed * 115.0).min(100.0);
+            result.round() as u8
+        })
     }
 
-    /// Setup the monitor stream for the default sink (Output Volume Level)
+    /// Setup the monitor stream for the default sink, or the --monitor-sink
+    /// one (Output Volume Level)
     fn setup_monitor_stream(&mut self, default_sink_name: Option<String>) {
         // Destroy existing stream if it exists
         if let Some(stream_rc) = self.monitor_stream.take() {
@@ -314,10 +669,15 @@ impl PulseAudioActor {
             self.mainloop.borrow_mut().unlock();
         }
 
+        self.monitored_sink = default_sink_name.clone();
         let Some(default_sink_name) = default_sink_name else {
-            self.peak_level.store(0, Ordering::Relaxed);
+            self.peak_left.store(0, Ordering::Relaxed);
+            self.peak_right.store(0, Ordering::Relaxed);
             return;
         };
+        if self.meters_suspended {
+            return;
+        }
 
         // Monitor stream reads from the default sink's monitor source
         let monitor_source_name = format!("{}.monitor", default_sink_name);
@@ -336,7 +696,7 @@ impl PulseAudioActor {
         self.mainloop.borrow_mut().lock();
 
         let stream = match Stream::new(
-            &mut *self.context.borrow_mut(),
+            &mut self.context.borrow_mut(),
             "EWW Mixer Sink Monitor Stream", // Updated name
             &spec,
             None,
@@ -349,7 +709,9 @@ impl PulseAudioActor {
             }
         };
 
-        let peak_level_clone = Arc::clone(&self.peak_level);
+        let peak_left_clone = Arc::clone(&self.peak_left);
+        let peak_right_clone = Arc::clone(&self.peak_right);
+        let mut errors = PeekErrorLog::new("Sink Monitor Stream", Arc::clone(&self.monitor_failed));
         let stream_rc = Rc::new(RefCell::new(stream));
         let stream_clone = Rc::clone(&stream_rc);
 
@@ -362,20 +724,18 @@ impl PulseAudioActor {
 
             match stream_ref.peek() {
                 Ok(peek_result) => {
-                    match peek_result {
-                        libpulse_binding::stream::PeekResult::Data(data_slice) => {
-                            if !data_slice.is_empty() && data_slice.len() % 2 == 0 {
-                                let peak = PulseAudioActor::calculate_peak_volume(data_slice);
-                                peak_level_clone.fetch_max(peak, Ordering::Relaxed);
-                            }
+                    if let libpulse_binding::stream::PeekResult::Data(data_slice) = peek_result {
+                        if !data_slice.is_empty() {
+                            let [left, right] =
+                                PulseAudioActor::calculate_peak_volume::<2>(data_slice);
+                            peak_left_clone.fetch_max(left, Ordering::Relaxed);
+                            peak_right_clone.fetch_max(right, Ordering::Relaxed);
                         }
-                        _ => {}
                     }
                     stream_ref.discard().unwrap_or_default();
+                    errors.ok();
                 }
-                Err(e) => {
-                    eprintln!("Sink Monitor Stream peek error: {:?}", e);
-                }
+                Err(e) => errors.error(e),
             }
         };
 
@@ -403,10 +763,14 @@ impl PulseAudioActor {
             self.mainloop.borrow_mut().unlock();
         }
 
+        self.mic_monitored_source = default_source_name.clone();
         let Some(default_source_name) = default_source_name else {
             self.mic_peak_level.store(0, Ordering::Relaxed);
             return;
         };
+        if self.meters_suspended {
+            return;
+        }
 
         // Monitor stream reads directly from the default source
         let source_name = default_source_name;
@@ -425,7 +789,7 @@ impl PulseAudioActor {
         self.mainloop.borrow_mut().lock();
 
         let stream = match Stream::new(
-            &mut *self.context.borrow_mut(),
+            &mut self.context.borrow_mut(),
             "EWW Mixer Source Monitor Stream", // Updated name
             &spec,
             None,
@@ -439,6 +803,10 @@ impl PulseAudioActor {
         };
 
         let peak_level_clone = Arc::clone(&self.mic_peak_level);
+        let mut errors = PeekErrorLog::new(
+            "Source Monitor Stream",
+            Arc::clone(&self.mic_monitor_failed),
+        );
         let stream_rc = Rc::new(RefCell::new(stream));
         let stream_clone = Rc::clone(&stream_rc);
 
@@ -451,20 +819,16 @@ impl PulseAudioActor {
 
             match stream_ref.peek() {
                 Ok(peek_result) => {
-                    match peek_result {
-                        libpulse_binding::stream::PeekResult::Data(data_slice) => {
-                            if !data_slice.is_empty() && data_slice.len() % 2 == 0 {
-                                let peak = PulseAudioActor::calculate_peak_volume(data_slice);
-                                peak_level_clone.fetch_max(peak, Ordering::Relaxed);
-                            }
+                    if let libpulse_binding::stream::PeekResult::Data(data_slice) = peek_result {
+                        if !data_slice.is_empty() && data_slice.len() % 2 == 0 {
+                            let [peak] = PulseAudioActor::calculate_peak_volume::<1>(data_slice);
+                            peak_level_clone.fetch_max(peak, Ordering::Relaxed);
                         }
-                        _ => {}
                     }
                     stream_ref.discard().unwrap_or_default();
+                    errors.ok();
                 }
-                Err(e) => {
-                    eprintln!("Source Monitor Stream peek error: {:?}", e);
-                }
+                Err(e) => errors.error(e),
             }
         };
 
@@ -504,6 +868,7 @@ impl PulseAudioActor {
     /// Get complete mixer state with all devices and applications
     fn get_state(&mut self) -> MixerState {
         let mut state = MixerState::default();
+        let curve = self.config.volume_curve;
 
         let (tx, rx) = std::sync::mpsc::channel();
         self.mainloop.borrow_mut().lock();
@@ -515,7 +880,14 @@ impl PulseAudioActor {
         });
         self.mainloop.borrow_mut().unlock();
 
-        let (default_sink_name, default_source_name) = rx.recv().unwrap_or((None, None));
+        let (sink, source) = rx.recv().unwrap_or((None, None));
+        // Descriptions from the previous snapshot let a renamed default still be found
+        let defaults = DefaultNames {
+            sink_description: known_description(&self.last_state.sinks, sink.as_deref()),
+            source_description: known_description(&self.last_state.sources, source.as_deref()),
+            sink,
+            source,
+        };
 
         // Get all sinks
         self.mainloop.borrow_mut().lock();
@@ -523,23 +895,8 @@ impl PulseAudioActor {
         let (tx, rx) = std::sync::mpsc::channel();
         introspect.get_sink_info_list(move |res| match res {
             ListResult::Item(item) => {
-                let vol = (item.volume.avg().0 as f64 / Volume::NORMAL.0 as f64 * 100.0) as u8;
-                let _ = tx.send(Some(SinkInfo {
-                    index: item.index,
-                    name: item
-                        .name
-                        .as_ref()
-                        .map(|s| s.to_string())
-                        .unwrap_or_default(),
-                    description: item
-                        .description
-                        .as_ref()
-                        .map(|s| s.to_string())
-                        .unwrap_or_default(),
-                    volume: vol,
-                    muted: item.mute,
-                    is_default: false,
-                }));
+                let running = item.state == SinkState::Running;
+                let _ = tx.send(Some((running, sink_info(item, curve))));
             }
             ListResult::End => {
                 let _ = tx.send(None);
@@ -548,8 +905,11 @@ impl PulseAudioActor {
         });
         self.mainloop.borrow_mut().unlock();
 
-        while let Ok(Some(mut sink)) = rx.recv() {
-            sink.is_default = Some(&sink.name) == default_sink_name.as_ref();
+        self.running_devices.clear();
+        while let Ok(Some((running, sink))) = rx.recv() {
+            if running {
+                self.running_devices.push(sink.name.clone());
+            }
             state.sinks.push(sink);
         }
 
@@ -559,18 +919,7 @@ impl PulseAudioActor {
         let (tx, rx) = std::sync::mpsc::channel();
         introspect.get_sink_input_info_list(move |res| match res {
             ListResult::Item(item) => {
-                let vol = (item.volume.avg().0 as f64 / Volume::NORMAL.0 as f64 * 100.0) as u8;
-                let name = item
-                    .proplist
-                    .get_str(libpulse_binding::proplist::properties::APPLICATION_NAME)
-                    .unwrap_or_else(|| "Unknown".to_string());
-                let _ = tx.send(Some(SinkInputInfo {
-                    index: item.index,
-                    name,
-                    volume: vol,
-                    muted: item.mute,
-                    sink_index: item.sink,
-                }));
+                let _ = tx.send(Some(sink_input_info(item, curve)));
             }
             ListResult::End => {
                 let _ = tx.send(None);
@@ -594,23 +943,8 @@ impl PulseAudioActor {
                         return;
                     }
                 }
-                let vol = (item.volume.avg().0 as f64 / Volume::NORMAL.0 as f64 * 100.0) as u8;
-                let _ = tx.send(Some(SourceInfo {
-                    index: item.index,
-                    name: item
-                        .name
-                        .as_ref()
-                        .map(|s| s.to_string())
-                        .unwrap_or_default(),
-                    description: item
-                        .description
-                        .as_ref()
-                        .map(|s| s.to_string())
-                        .unwrap_or_default(),
-                    volume: vol,
-                    muted: item.mute,
-                    is_default: false,
-                }));
+                let running = item.state == SourceState::Running;
+                let _ = tx.send(Some((running, source_info(item, curve))));
             }
             ListResult::End => {
                 let _ = tx.send(None);
@@ -619,8 +953,10 @@ impl PulseAudioActor {
         });
         self.mainloop.borrow_mut().unlock();
 
-        while let Ok(Some(mut source)) = rx.recv() {
-            source.is_default = Some(&source.name) == default_source_name.as_ref();
+        while let Ok(Some((running, source))) = rx.recv() {
+            if running {
+                self.running_devices.push(source.name.clone());
+            }
             state.sources.push(source);
         }
 
@@ -630,18 +966,7 @@ impl PulseAudioActor {
         let (tx, rx) = std::sync::mpsc::channel();
         introspect.get_source_output_info_list(move |res| match res {
             ListResult::Item(item) => {
-                let vol = (item.volume.avg().0 as f64 / Volume::NORMAL.0 as f64 * 100.0) as u8;
-                let name = item
-                    .proplist
-                    .get_str(libpulse_binding::proplist::properties::APPLICATION_NAME)
-                    .unwrap_or_else(|| "Unknown".to_string());
-                let _ = tx.send(Some(SourceOutputInfo {
-                    index: item.index,
-                    name,
-                    volume: vol,
-                    muted: item.mute,
-                    source_index: item.source,
-                }));
+                let _ = tx.send(Some(source_output_info(item, curve)));
             }
             ListResult::End => {
                 let _ = tx.send(None);
@@ -654,35 +979,22 @@ impl PulseAudioActor {
             state.source_outputs.push(output);
         }
 
-        state.sinks.sort_by(|a, b| b.is_default.cmp(&a.is_default));
-        state
-            .sources
-            .sort_by(|a, b| b.is_default.cmp(&a.is_default));
-
-        if let Some(def) = state.sinks.iter().find(|s| s.is_default) {
-            state.volume_percent = def.volume;
-            state.volume_muted = def.muted;
-        } else if let Some(first) = state.sinks.first() {
-            state.volume_percent = first.volume;
-            state.volume_muted = first.muted;
-        }
-
-        if let Some(def) = state.sources.iter().find(|s| s.is_default) {
-            state.mic_percent = def.volume;
-            state.mic_muted = def.muted;
-        } else if let Some(first) = state.sources.first() {
-            state.mic_percent = first.volume;
-            state.mic_muted = first.muted;
-        }
+        finalize_state(&mut state, &self.config, &defaults);
 
-        state.volume_level = self.peak_level.load(Ordering::Relaxed);
+        state.volume_level_left = self.peak_left.load(Ordering::Relaxed);
+        state.volume_level_right = self.peak_right.load(Ordering::Relaxed);
+        state.volume_level = state.volume_level_left.max(state.volume_level_right);
         state.mic_level = self.mic_peak_level.load(Ordering::Relaxed);
 
         state
     }
 
-    /// Set volume for any audio target
-    fn set_volume(&mut self, target: AudioTarget, index: u32, percent: u8) -> Result<(), String> {
+    /// Current per-channel volumes of any audio target, with its channel map
+    fn get_channel_volumes(
+        &self,
+        target: AudioTarget,
+        index: u32,
+    ) -> Option<(ChannelVolumes, ChannelMap)> {
         self.mainloop.borrow_mut().lock();
 
         let (tx, rx) = std::sync::mpsc::channel();
@@ -692,7 +1004,7 @@ impl PulseAudioActor {
             AudioTarget::Sink => {
                 introspect.get_sink_info_by_index(index, move |res| {
                     if let ListResult::Item(item) = res {
-                        let _ = tx.send(Some(item.volume));
+                        let _ = tx.send(Some((item.volume, item.channel_map)));
                     } else {
                         let _ = tx.send(None);
                     }
@@ -701,7 +1013,7 @@ impl PulseAudioActor {
             AudioTarget::SinkInput => {
                 introspect.get_sink_input_info(index, move |res| {
                     if let ListResult::Item(item) = res {
-                        let _ = tx.send(Some(item.volume));
+                        let _ = tx.send(Some((item.volume, item.channel_map)));
                     } else {
                         let _ = tx.send(None);
                     }
@@ -710,7 +1022,7 @@ impl PulseAudioActor {
             AudioTarget::Source => {
                 introspect.get_source_info_by_index(index, move |res| {
                     if let ListResult::Item(item) = res {
-                        let _ = tx.send(Some(item.volume));
+                        let _ = tx.send(Some((item.volume, item.channel_map)));
                     } else {
                         let _ = tx.send(None);
                     }
@@ -719,7 +1031,7 @@ impl PulseAudioActor {
             AudioTarget::SourceOutput => {
                 introspect.get_source_output_info(index, move |res| {
                     if let ListResult::Item(item) = res {
-                        let _ = tx.send(Some(item.volume));
+                        let _ = tx.send(Some((item.volume, item.channel_map)));
                     } else {
                         let _ = tx.send(None);
                     }
@@ -729,38 +1041,175 @@ impl PulseAudioActor {
 
         self.mainloop.borrow_mut().unlock();
 
-        if let Ok(Some(mut volumes)) = rx.recv() {
-            self.mainloop.borrow_mut().lock();
-            let vol_value = (Volume::NORMAL.0 as f64 * percent.min(100) as f64 / 100.0) as u32;
-            volumes.scale(Volume(vol_value));
+        rx.recv().ok().flatten()
+    }
 
-            let mut introspect = self.context.borrow().introspect();
-            match target {
-                AudioTarget::Sink => {
-                    introspect.set_sink_volume_by_index(index, &volumes, None);
-                }
-                AudioTarget::SinkInput => {
-                    introspect.set_sink_input_volume(index, &volumes, None);
-                }
-                AudioTarget::Source => {
-                    introspect.set_source_volume_by_index(index, &volumes, None);
-                }
-                AudioTarget::SourceOutput => {
-                    introspect.set_source_output_volume(index, &volumes, None);
-                }
+    /// Write per-channel volumes back to any audio target
+    fn apply_channel_volumes(&self, target: AudioTarget, index: u32, volumes: &ChannelVolumes) {
+        self.mainloop.borrow_mut().lock();
+        let mut introspect = self.context.borrow().introspect();
+        match target {
+            AudioTarget::Sink => {
+                introspect.set_sink_volume_by_index(index, volumes, None);
             }
-            self.mainloop.borrow_mut().unlock();
-            Ok(())
-        } else {
-            Err(format!(
+            AudioTarget::SinkInput => {
+                introspect.set_sink_input_volume(index, volumes, None);
+            }
+            AudioTarget::Source => {
+                introspect.set_source_volume_by_index(index, volumes, None);
+            }
+            AudioTarget::SourceOutput => {
+                introspect.set_source_output_volume(index, volumes, None);
+            }
+        }
+        self.mainloop.borrow_mut().unlock();
+    }
+
+    /// Turn DEFAULT_INDEX into the index of the current default device
+    fn resolve_index(&mut self, target: AudioTarget, index: u32) -> Result<u32, String> {
+        if index != DEFAULT_INDEX {
+            return Ok(index);
+        }
+        default_index(&self.get_state(), target)
+    }
+
+    /// Query one target by index, bypassing --ignore-app and --max-inputs
+    fn describe(&mut self, target: AudioTarget, index: u32) -> Result<TargetInfo, String> {
+        let state = self.get_state();
+        let default = default_index(&state, target).ok();
+        let index = match index {
+            DEFAULT_INDEX => default_index(&state, target)?,
+            index => index,
+        };
+        let curve = self.config.volume_curve;
+
+        self.mainloop.borrow_mut().lock();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let introspect = self.context.borrow().introspect();
+        match target {
+            AudioTarget::Sink => {
+                introspect.get_sink_info_by_index(index, move |res| {
+                    let info = match res {
+                        ListResult::Item(item) => Some(TargetInfo::Sink(SinkInfo {
+                            is_default: default == Some(item.index),
+                            ..sink_info(item, curve)
+                        })),
+                        _ => None,
+                    };
+                    let _ = tx.send(info);
+                });
+            }
+            AudioTarget::SinkInput => {
+                introspect.get_sink_input_info(index, move |res| {
+                    let info = match res {
+                        ListResult::Item(item) => {
+                            Some(TargetInfo::SinkInput(sink_input_info(item, curve)))
+                        }
+                        _ => None,
+                    };
+                    let _ = tx.send(info);
+                });
+            }
+            AudioTarget::Source => {
+                introspect.get_source_info_by_index(index, move |res| {
+                    let info = match res {
+                        ListResult::Item(item) => Some(TargetInfo::Source(SourceInfo {
+                            is_default: default == Some(item.index),
+                            ..source_info(item, curve)
+                        })),
+                        _ => None,
+                    };
+                    let _ = tx.send(info);
+                });
+            }
+            AudioTarget::SourceOutput => {
+                introspect.get_source_output_info(index, move |res| {
+                    let info = match res {
+                        ListResult::Item(item) => {
+                            Some(TargetInfo::SourceOutput(source_output_info(item, curve)))
+                        }
+                        _ => None,
+                    };
+                    let _ = tx.send(info);
+                });
+            }
+        }
+        self.mainloop.borrow_mut().unlock();
+
+        match rx.recv().ok().flatten() {
+            Some(info) => Ok(info),
+            None => Err(target_not_found(&state, target, index)),
+        }
+    }
+
+    /// Set volume for any audio target
+    fn set_volume(&mut self, target: AudioTarget, index: u32, percent: u8) -> Result<(), String> {
+        let index = self.resolve_index(target, index)?;
+        let Some((mut volumes, _)) = self.get_channel_volumes(target, index) else {
+            return Err(format!(
                 "Failed to get current volume for {:?} {}",
                 target, index
-            ))
+            ));
+        };
+
+        volumes.scale(self.config.volume_curve.to_volume(percent.min(100)));
+        self.apply_channel_volumes(target, index, &volumes);
+        Ok(())
+    }
+
+    /// Set the volume of one channel, leaving the others untouched
+    fn set_channel_volume(
+        &mut self,
+        target: AudioTarget,
+        index: u32,
+        channel: u8,
+        percent: u8,
+    ) -> Result<(), String> {
+        let Some((mut volumes, _)) = self.get_channel_volumes(target, index) else {
+            return Err(format!(
+                "Failed to get current volume for {:?} {}",
+                target, index
+            ));
+        };
+
+        let channels = volumes.len();
+        let Some(volume) = volumes.get_mut().get_mut(channel as usize) else {
+            return Err(format!(
+                "Channel {} out of range for {:?} {} ({} channels)",
+                channel, target, index, channels
+            ));
+        };
+        *volume = self.config.volume_curve.to_volume(percent.min(100));
+
+        self.apply_channel_volumes(target, index, &volumes);
+        Ok(())
+    }
+
+    /// Pan any audio target; targets whose channel map has no left/right
+    /// (e.g. mono) stay centred and still succeed
+    fn set_balance(&mut self, target: AudioTarget, index: u32, balance: i8) -> Result<(), String> {
+        let index = self.resolve_index(target, index)?;
+        let Some((mut volumes, map)) = self.get_channel_volumes(target, index) else {
+            return Err(format!(
+                "Failed to get current volume for {:?} {}",
+                target, index
+            ));
+        };
+        if !map.can_balance() {
+            return Ok(());
+        }
+
+        let balance = f32::from(balance.clamp(-100, 100)) / 100.0;
+        if volumes.set_balance(&map, balance).is_none() {
+            return Err(format!("Failed to set balance for {:?} {}", target, index));
         }
+        self.apply_channel_volumes(target, index, &volumes);
+        Ok(())
     }
 
     /// Toggle mute for any audio target
     fn toggle_mute(&mut self, target: AudioTarget, index: u32) -> Result<(), String> {
+        let index = self.resolve_index(target, index)?;
         self.mainloop.borrow_mut().lock();
 
         let introspect = self.context.borrow().introspect();
@@ -861,7 +1310,7 @@ impl PulseAudioActor {
 
         if rx.recv().unwrap_or(false) {
             // If the default device was changed, update the monitor streams
-            if target == DefaultTarget::Sink {
+            if target == DefaultTarget::Sink && self.config.monitor_sink.is_none() {
                 self.setup_monitor_stream(Some(name.to_string()));
             } else if target == DefaultTarget::Source {
                 self.setup_mic_monitor_stream(Some(name.to_string()));
@@ -872,6 +1321,40 @@ impl PulseAudioActor {
         }
     }
 
+    /// Unmute the default source while held; restore the saved mute on release
+    fn push_to_talk(&mut self, press: bool) -> Result<(), String> {
+        if !press {
+            // Release without a matching press is a no-op
+            let Some((index, was_muted)) = self.ptt_saved.take() else {
+                return Ok(());
+            };
+            return self.set_source_mute(index, was_muted);
+        }
+
+        // Key repeat sends more presses; keep the state saved by the first one
+        let (index, _) = match self.ptt_saved {
+            Some(saved) => saved,
+            None => {
+                let state = self.get_state();
+                let source = state
+                    .sources
+                    .iter()
+                    .find(|s| s.is_default)
+                    .ok_or_else(|| "No default source for push-to-talk".to_string())?;
+                *self.ptt_saved.insert((source.index, source.muted))
+            }
+        };
+        self.set_source_mute(index, false)
+    }
+
+    fn set_source_mute(&mut self, index: u32, mute: bool) -> Result<(), String> {
+        self.mainloop.borrow_mut().lock();
+        let mut introspect = self.context.borrow().introspect();
+        introspect.set_source_mute_by_index(index, mute, None);
+        self.mainloop.borrow_mut().unlock();
+        Ok(())
+    }
+
     /// Broadcast state update to all listeners if changed
     fn broadcast_state_if_changed(&mut self) {
         let new_state = self.get_state();
@@ -883,17 +1366,129 @@ impl PulseAudioActor {
         }
     }
 
+    /// Lower both peak levels proportionally to the time since the last decay
+    fn decay_peak_levels(&self, elapsed: std::time::Duration) {
+        let step = (elapsed.as_millis() as u64 * PEAK_DECAY_STEP / PEAK_DECAY_INTERVAL_MS)
+            .min(u8::MAX as u64) as u8;
+
+        for level in [&self.peak_left, &self.peak_right, &self.mic_peak_level] {
+            let current = level.load(Ordering::Relaxed);
+            if current > 0 {
+                level.store(current.saturating_sub(step), Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Broadcast only the meter levels, reusing the last enumerated device state
+    fn broadcast_levels_if_changed(&mut self) {
+        let left = self.peak_left.load(Ordering::Relaxed);
+        let right = self.peak_right.load(Ordering::Relaxed);
+        let mic_level = self.mic_peak_level.load(Ordering::Relaxed);
+
+        if left != self.last_state.volume_level_left
+            || right != self.last_state.volume_level_right
+            || mic_level != self.last_state.mic_level
+        {
+            self.last_state.volume_level_left = left;
+            self.last_state.volume_level_right = right;
+            self.last_state.volume_level = left.max(right);
+            self.last_state.mic_level = mic_level;
+            if let Some(tx) = &self.broadcast_tx {
+                let _ = tx.send(self.last_state.clone());
+            }
+        }
+    }
+
+    /// Point the sink monitor at the --monitor-sink sink while it exists and
+    /// drop it (zeroing the meter) while it doesn't
+    fn follow_monitor_sink(&mut self) {
+        let Some(name) = self.config.monitor_sink.clone() else {
+            return;
+        };
+        let present = self.last_state.sinks.iter().any(|s| s.name == name);
+        if present && self.monitored_sink.as_ref() != Some(&name) {
+            self.setup_monitor_stream(Some(name));
+        } else if !present && self.monitored_sink.is_some() {
+            self.setup_monitor_stream(None);
+        }
+    }
+
+    /// Recreate monitor streams whose read callbacks keep failing, at most
+    /// once per log interval
+    fn rebuild_failed_monitors(&mut self) {
+        let interval = std::time::Duration::from_millis(PEEK_ERROR_LOG_INTERVAL_MS);
+        if self.last_monitor_rebuild.elapsed() < interval {
+            return;
+        }
+
+        if self.monitor_failed.swap(false, Ordering::Relaxed) {
+            eprintln!("Sink monitor stream keeps failing, rebuilding it");
+            self.setup_monitor_stream(self.monitored_sink.clone());
+            self.last_monitor_rebuild = std::time::Instant::now();
+        }
+        if self.mic_monitor_failed.swap(false, Ordering::Relaxed) {
+            eprintln!("Source monitor stream keeps failing, rebuilding it");
+            self.setup_mic_monitor_stream(self.mic_monitored_source.clone());
+            self.last_monitor_rebuild = std::time::Instant::now();
+        }
+    }
+
+    /// Record meter activity, reconnecting the monitors if they were suspended
+    fn note_meter_activity(&mut self) {
+        self.last_meter_activity = std::time::Instant::now();
+        if self.meters_suspended {
+            eprintln!("Resuming peak monitors");
+            self.meters_suspended = false;
+            self.setup_monitor_stream(self.monitored_sink.clone());
+            self.setup_mic_monitor_stream(self.mic_monitored_source.clone());
+        }
+    }
+
+    /// Disconnect both monitors once --meter-idle-timeout passes without activity
+    fn suspend_idle_meters(&mut self) {
+        let Some(timeout) = self.config.meter_idle_timeout else {
+            return;
+        };
+        if self.meters_suspended
+            || self.last_meter_activity.elapsed() < std::time::Duration::from_secs(timeout)
+        {
+            return;
+        }
+
+        eprintln!("No peaks for {}s, suspending peak monitors", timeout);
+        self.meters_suspended = true;
+        self.setup_monitor_stream(self.monitored_sink.clone());
+        self.setup_mic_monitor_stream(self.mic_monitored_source.clone());
+    }
+
+    /// Whether something is playing to the metered sink or recording from the
+    /// default source, as of the last get_state. Only meaningful while our own
+    /// monitors are disconnected.
+    fn audio_running(&self) -> bool {
+        [&self.monitored_sink, &self.mic_monitored_source]
+            .into_iter()
+            .flatten()
+            .any(|name| self.running_devices.contains(name))
+    }
+
     /// Main actor loop - processes commands from sync thread
     fn run_actor_loop(mut self, rx: std::sync::mpsc::Receiver<ActorCommand>) {
         self.setup_event_subscription();
 
         // Setup initial monitor streams
         let initial_state = self.get_state();
-        let default_sink_name = initial_state
-            .sinks
-            .iter()
-            .find(|s| s.is_default)
-            .map(|s| s.name.clone());
+        let monitored_sink_name = match &self.config.monitor_sink {
+            Some(name) => initial_state
+                .sinks
+                .iter()
+                .find(|s| &s.name == name)
+                .map(|s| s.name.clone()),
+            None => initial_state
+                .sinks
+                .iter()
+                .find(|s| s.is_default)
+                .map(|s| s.name.clone()),
+        };
         let default_source_name = initial_state
             .sources
             .iter()
@@ -901,54 +1496,97 @@ impl PulseAudioActor {
             .map(|s| s.name.clone());
 
         // Initialize both monitor streams
-        self.setup_monitor_stream(default_sink_name);
+        self.setup_monitor_stream(monitored_sink_name);
         self.setup_mic_monitor_stream(default_source_name);
 
+        let update_interval =
+            std::time::Duration::from_millis(self.config.update_interval_ms.max(1));
+        let meter_interval = std::time::Duration::from_millis(
+            self.config
+                .meter_interval_ms
+                .unwrap_or(self.config.update_interval_ms)
+                .max(1),
+        );
+        let tick = update_interval.min(meter_interval);
+
         let mut last_update = std::time::Instant::now();
-        let mut last_decay = std::time::Instant::now();
+        let mut last_meter = std::time::Instant::now();
 
         loop {
-            match rx.recv_timeout(std::time::Duration::from_millis(16)) {
-                // ~60fps
-                Ok(cmd) => match cmd {
-                    ActorCommand::GetState(response) => {
-                        let state = self.get_state();
-                        let _ = response.send(state);
-                    }
-                    ActorCommand::SetVolume {
-                        target,
-                        index,
-                        percent,
-                        response,
-                    } => {
-                        let result = self.set_volume(target, index, percent);
-                        let _ = response.send(result);
-                        self.broadcast_state_if_changed();
-                    }
-                    ActorCommand::ToggleMute {
-                        target,
-                        index,
-                        response,
-                    } => {
-                        let result = self.toggle_mute(target, index);
-                        let _ = response.send(result);
-                        self.broadcast_state_if_changed();
-                    }
-                    ActorCommand::SetDefault {
-                        target,
-                        name,
-                        response,
-                    } => {
-                        let result = self.set_default(target, &name);
-                        let _ = response.send(result);
-
-                        // Monitor stream update is now handled inside set_default
-                        self.broadcast_state_if_changed();
-                    }
-                    ActorCommand::Subscribe => {
-                        // Subscription handled via separate channel
-                    }
-                },
+            match rx.recv_timeout(tick) {
+                Ok(cmd) => {
+                    // A client command means someone is looking at the mixer
+                    self.note_meter_activity();
+                    match cmd {
+                        ActorCommand::GetState(response) => {
+                            let state = self.get_state();
+                            let _ = response.send(state);
+                        }
+                        ActorCommand::SetVolume {
+                            target,
+                            index,
+                            percent,
+                            response,
+                        } => {
+                            let result = self.set_volume(target, index, percent);
+                            let _ = response.send(result);
+                            self.broadcast_state_if_changed();
+                        }
+                        ActorCommand::SetChannelVolume {
+                            target,
+                            index,
+                            channel,
+                            percent,
+                            response,
+                        } => {
+                            let result = self.set_channel_volume(target, index, channel, percent);
+                            let _ = response.send(result);
+                            self.broadcast_state_if_changed();
+                        }
+                        ActorCommand::SetBalance {
+                            target,
+                            index,
+                            balance,
+                            response,
+                        } => {
+                            let result = self.set_balance(target, index, balance);
+                            let _ = response.send(result);
+                            self.broadcast_state_if_changed();
+                        }
+                        ActorCommand::ToggleMute {
+                            target,
+                            index,
+                            response,
+                        } => {
+                            let result = self.toggle_mute(target, index);
+                            let _ = response.send(result);
+                            self.broadcast_state_if_changed();
+                        }
+                        ActorCommand::SetDefault {
+                            target,
+                            name,
+                            response,
+                        } => {
+                            let result = self.set_default(target, &name);
+                            let _ = response.send(result);
+
+                            // Monitor stream update is now handled inside set_default
+                            self.broadcast_state_if_changed();
+                        }
+                        ActorCommand::PushToTalk { press, response } => {
+                            let result = self.push_to_talk(press);
+                            let _ = response.send(result);
+                            self.broadcast_state_if_changed();
+                        }
+                        ActorCommand::Describe {
+                            target,
+                            index,
+                            response,
+                        } => {
+                            let _ = response.send(self.describe(target, index));
+                        }
+                    }
+                }
                 Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
                     // Timeout - continue to updates
                 }
@@ -957,33 +1595,413 @@ impl PulseAudioActor {
                 }
             }
 
-            // Decay peak levels smoothly
-            if last_decay.elapsed() > std::time::Duration::from_millis(50) {
-                let current = self.peak_level.load(Ordering::Relaxed);
-                if current > 0 {
-                    let new_val = current.saturating_sub(3);
-                    self.peak_level.store(new_val, Ordering::Relaxed);
-                }
-                let current_mic = self.mic_peak_level.load(Ordering::Relaxed);
-                if current_mic > 0 {
-                    let new_val = current_mic.saturating_sub(3);
-                    self.mic_peak_level.store(new_val, Ordering::Relaxed);
+            // Decay peak levels smoothly and push meter-only updates
+            if last_meter.elapsed() >= meter_interval {
+                let loudest = [&self.peak_left, &self.peak_right, &self.mic_peak_level]
+                    .iter()
+                    .map(|level| level.load(Ordering::Relaxed))
+                    .max()
+                    .unwrap_or(0);
+                if loudest > METER_IDLE_PEAK || self.connected_clients.load(Ordering::Relaxed) > 0 {
+                    self.note_meter_activity();
+                } else {
+                    self.suspend_idle_meters();
                 }
 
-                last_decay = std::time::Instant::now();
+                self.decay_peak_levels(last_meter.elapsed());
+                self.rebuild_failed_monitors();
+                last_meter = std::time::Instant::now();
+
+                if last_update.elapsed() < update_interval {
+                    self.broadcast_levels_if_changed();
+                }
             }
 
             // Periodic state broadcast
-            if last_update.elapsed() > std::time::Duration::from_millis(STATE_UPDATE_INTERVAL_MS) {
+            if last_update.elapsed() >= update_interval {
                 self.broadcast_state_if_changed();
+                self.follow_monitor_sink();
+                if self.meters_suspended && self.audio_running() {
+                    self.note_meter_activity();
+                }
                 last_update = std::time::Instant::now();
             }
         }
     }
 }
+
+/// The actor serving client commands, by audio server
+enum AudioBackend {
+    PulseAudio(Box<PulseAudioActor>),
+    #[cfg(feature = "pipewire")]
+    PipeWire(pipewire::PipeWireActor),
+}
+
+/// Serve the "no audio server" state until PulseAudio (or, with the pipewire
+/// feature, PipeWire) can be reached, retrying in the background. Returns None
+/// once the command channel closes.
+fn wait_for_audio_server(
+    config: &DaemonConfig,
+    rx: &std::sync::mpsc::Receiver<ActorCommand>,
+) -> Option<AudioBackend> {
+    let retry_interval = std::time::Duration::from_millis(AUDIO_RETRY_INTERVAL_MS);
+    let mut last_attempt = std::time::Instant::now();
+
+    loop {
+        match rx.recv_timeout(retry_interval) {
+            Ok(cmd) => reject_without_audio(cmd),
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return None,
+        }
+
+        if last_attempt.elapsed() >= retry_interval {
+            last_attempt = std::time::Instant::now();
+            if let Ok(actor) = PulseAudioActor::new(config.clone()) {
+                eprintln!("PulseAudio is back, resuming normal operation");
+                return Some(AudioBackend::PulseAudio(Box::new(actor)));
+            }
+            #[cfg(feature = "pipewire")]
+            if pipewire::is_available() {
+                eprintln!("PipeWire is up, using PipeWire backend");
+                let actor = pipewire::PipeWireActor::new(config.clone());
+                return Some(AudioBackend::PipeWire(actor));
+            }
+        }
+    }
+}
+
+/// Answer a command while no audio server is available
+fn reject_without_audio(cmd: ActorCommand) {
+    let unavailable = || Err("No audio server available".to_string());
+    match cmd {
+        ActorCommand::GetState(response) => {
+            let _ = response.send(MixerState::default());
+        }
+        ActorCommand::SetVolume { response, .. }
+        | ActorCommand::SetChannelVolume { response, .. }
+        | ActorCommand::SetBalance { response, .. }
+        | ActorCommand::ToggleMute { response, .. }
+        | ActorCommand::SetDefault { response, .. }
+        | ActorCommand::PushToTalk { response, .. } => {
+            let _ = response.send(unavailable());
+        }
+        ActorCommand::Describe { response, .. } => {
+            let _ = response.send(Err("No audio server available".to_string()));
+        }
+    }
+}
+
+/// Per-channel volumes as 0-100 percentages
+fn channel_percents(volumes: &ChannelVolumes, curve: VolumeCurve) -> Vec<u8> {
+    volumes
+        .get()
+        .iter()
+        .map(|&v| curve.volume_percent(v))
+        .collect()
+}
+
+/// Left/right balance as -100..=100; 0 for maps without both sides
+fn balance_percent(volumes: &ChannelVolumes, map: &ChannelMap) -> i8 {
+    (volumes.get_balance(map).clamp(-1.0, 1.0) * 100.0).round() as i8
+}
+
+/// Machine-readable channel position names, e.g. "front-left"
+fn channel_labels(map: &ChannelMap) -> Vec<String> {
+    map.get()
+        .iter()
+        .map(|&pos| Position::to_string(pos).unwrap_or_default())
+        .collect()
+}
+
+fn sink_info(item: &introspect::SinkInfo, curve: VolumeCurve) -> SinkInfo {
+    SinkInfo {
+        index: item.index,
+        name: item
+            .name
+            .as_ref()
+            .map(|s| s.to_string())
+            .unwrap_or_default(),
+        description: item
+            .description
+            .as_ref()
+            .map(|s| s.to_string())
+            .unwrap_or_default(),
+        volume: curve.volume_percent(item.volume.avg()),
+        muted: item.mute,
+        is_default: false,
+        channel_volumes: channel_percents(&item.volume, curve),
+        channel_labels: channel_labels(&item.channel_map),
+        balance: balance_percent(&item.volume, &item.channel_map),
+    }
+}
+
+fn sink_input_info(item: &introspect::SinkInputInfo, curve: VolumeCurve) -> SinkInputInfo {
+    SinkInputInfo {
+        index: item.index,
+        name: item
+            .proplist
+            .get_str(libpulse_binding::proplist::properties::APPLICATION_NAME)
+            .unwrap_or_else(|| "Unknown".to_string()),
+        volume: curve.volume_percent(item.volume.avg()),
+        muted: item.mute,
+        sink_index: item.sink,
+        balance: balance_percent(&item.volume, &item.channel_map),
+        channel_labels: channel_labels(&item.channel_map),
+    }
+}
+
+fn source_info(item: &introspect::SourceInfo, curve: VolumeCurve) -> SourceInfo {
+    SourceInfo {
+        index: item.index,
+        name: item
+            .name
+            .as_ref()
+            .map(|s| s.to_string())
+            .unwrap_or_default(),
+        description: item
+            .description
+            .as_ref()
+            .map(|s| s.to_string())
+            .unwrap_or_default(),
+        volume: curve.volume_percent(item.volume.avg()),
+        muted: item.mute,
+        is_default: false,
+        channel_volumes: channel_percents(&item.volume, curve),
+        channel_labels: channel_labels(&item.channel_map),
+    }
+}
+
+fn source_output_info(item: &introspect::SourceOutputInfo, curve: VolumeCurve) -> SourceOutputInfo {
+    SourceOutputInfo {
+        index: item.index,
+        name: item
+            .proplist
+            .get_str(libpulse_binding::proplist::properties::APPLICATION_NAME)
+            .unwrap_or_else(|| "Unknown".to_string()),
+        volume: curve.volume_percent(item.volume.avg()),
+        muted: item.mute,
+        source_index: item.source,
+        channel_labels: channel_labels(&item.channel_map),
+    }
+}
+
+/// Default device names, fetched once per state snapshot. The descriptions are
+/// a fallback for when the named device is gone (e.g. renamed by PipeWire).
+#[derive(Debug, Clone, Default)]
+struct DefaultNames {
+    sink: Option<String>,
+    source: Option<String>,
+    sink_description: Option<String>,
+    source_description: Option<String>,
+}
+
+/// Devices that carry an `is_default` flag
+trait DefaultFlag {
+    fn index(&self) -> u32;
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
+    fn is_default(&self) -> bool;
+    fn set_default(&mut self, is_default: bool);
+}
+
+impl DefaultFlag for SinkInfo {
+    fn index(&self) -> u32 {
+        self.index
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+    fn is_default(&self) -> bool {
+        self.is_default
+    }
+    fn set_default(&mut self, is_default: bool) {
+        self.is_default = is_default;
+    }
+}
+
+impl DefaultFlag for SourceInfo {
+    fn index(&self) -> u32 {
+        self.index
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+    fn is_default(&self) -> bool {
+        self.is_default
+    }
+    fn set_default(&mut self, is_default: bool) {
+        self.is_default = is_default;
+    }
+}
+
+/// Description of the default device in a previous listing: the device with that
+/// name, or else the one flagged default (which may have been matched by description)
+fn known_description<T: DefaultFlag>(items: &[T], default_name: Option<&str>) -> Option<String> {
+    items
+        .iter()
+        .find(|item| Some(item.name()) == default_name)
+        .or_else(|| items.iter().find(|item| item.is_default()))
+        .map(|item| item.description().to_string())
+        .filter(|description| !description.is_empty())
+}
+
+/// Pin the default device first and order the rest by `sort`
+fn sort_devices<T: DefaultFlag>(items: &mut [T], sort: DeviceSort) {
+    match sort {
+        DeviceSort::Default => {}
+        DeviceSort::Name => items.sort_by_cached_key(|item| item.description().to_lowercase()),
+        DeviceSort::Index => items.sort_by_key(|item| item.index()),
+    }
+    items.sort_by_key(|item| std::cmp::Reverse(item.is_default()));
+}
+
+/// Print a warning the first time this exact message comes up
+fn warn_once(message: String) {
+    static WARNED: std::sync::Mutex<std::collections::BTreeSet<String>> =
+        std::sync::Mutex::new(std::collections::BTreeSet::new());
+    if let Ok(mut warned) = WARNED.lock() {
+        if warned.insert(message.clone()) {
+            eprintln!("Warning: {}", message);
+        }
+    }
+}
+
+/// Flag at most one device as default: the first whose name matches, falling back
+/// to the first whose description matches when no name does
+fn mark_default<T: DefaultFlag>(
+    items: &mut [T],
+    default_name: Option<&str>,
+    default_description: Option<&str>,
+) {
+    let position = items
+        .iter()
+        .position(|item| Some(item.name()) == default_name)
+        .or_else(|| {
+            let description = default_description?;
+            let position = items
+                .iter()
+                .position(|item| item.description() == description)?;
+            warn_once(format!(
+                "default device '{}' not found, matched '{}' by description",
+                default_name.unwrap_or_default(),
+                items[position].name()
+            ));
+            Some(position)
+        });
+
+    for (i, item) in items.iter_mut().enumerate() {
+        item.set_default(Some(i) == position);
+    }
+}
+
+/// Resolve defaults, apply stream filters and caps, pin defaults first and fill the
+/// default-device summary. Shared by every backend so the JSON shape stays identical.
+fn finalize_state(state: &mut MixerState, config: &DaemonConfig, defaults: &DefaultNames) {
+    state.audio_available = true;
+    mark_default(
+        &mut state.sinks,
+        defaults.sink.as_deref(),
+        defaults.sink_description.as_deref(),
+    );
+    mark_default(
+        &mut state.sources,
+        defaults.source.as_deref(),
+        defaults.source_description.as_deref(),
+    );
+
+    if !config.ignore_apps.is_empty() {
+        state.sink_inputs.retain(|input| {
+            !config
+                .ignore_apps
+                .iter()
+                .any(|app| input.name.contains(app.as_str()))
+        });
+    }
+
+    if let Some(max) = config.max_inputs {
+        state.inputs_truncated = keep_newest(&mut state.sink_inputs, max, |i| i.index)
+            + keep_newest(&mut state.source_outputs, max, |o| o.index);
+    }
+
+    sort_devices(&mut state.sinks, config.sort);
+    sort_devices(&mut state.sources, config.sort);
+
+    if let Some(def) = state.sinks.iter().find(|s| s.is_default) {
+        state.volume_percent = def.volume;
+        state.volume_muted = def.muted;
+        state.default_sink_resolved = true;
+    } else if let Some(first) = state.sinks.first() {
+        state.volume_percent = first.volume;
+        state.volume_muted = first.muted;
+    }
+
+    if let Some(def) = state.sources.iter().find(|s| s.is_default) {
+        state.mic_percent = def.volume;
+        state.mic_muted = def.muted;
+        state.default_source_resolved = true;
+    } else if let Some(first) = state.sources.first() {
+        state.mic_percent = first.volume;
+        state.mic_muted = first.muted;
+    }
+}
+
+/// Keep only the `max` most recently created streams (highest index), in index order.
+/// Returns how many entries were dropped.
+fn keep_newest<T>(items: &mut Vec<T>, max: usize, index: impl Fn(&T) -> u32) -> u32 {
+    if items.len() <= max {
+        return 0;
+    }
+
+    let dropped = items.len() - max;
+    items.sort_by_key(|item| std::cmp::Reverse(index(item)));
+    items.truncate(max);
+    items.sort_by_key(|item| index(item));
+    dropped as u32
+}
+
 // ============================================================================
 // COMMAND CLIENT
 // ============================================================================
+/// Index of the default sink or source in a state snapshot
+fn default_index(state: &MixerState, target: AudioTarget) -> Result<u32, String> {
+    let index = match target {
+        AudioTarget::Sink => state.sinks.iter().find(|s| s.is_default).map(|s| s.index),
+        AudioTarget::Source => state.sources.iter().find(|s| s.is_default).map(|s| s.index),
+        AudioTarget::SinkInput | AudioTarget::SourceOutput => {
+            return Err(format!("{:?} needs a real index, not \"default\"", target));
+        }
+    };
+    index.ok_or_else(|| format!("No default {:?}", target))
+}
+
+/// Why a target is missing, listing the indices of that type the state does
+/// have, since a stale index is the usual cause
+fn target_not_found(state: &MixerState, target: AudioTarget, index: u32) -> String {
+    let known: Vec<u32> = match target {
+        AudioTarget::Sink => state.sinks.iter().map(|s| s.index).collect(),
+        AudioTarget::SinkInput => state.sink_inputs.iter().map(|s| s.index).collect(),
+        AudioTarget::Source => state.sources.iter().map(|s| s.index).collect(),
+        AudioTarget::SourceOutput => state.source_outputs.iter().map(|s| s.index).collect(),
+    };
+    let known: Vec<String> = known.iter().map(|i| i.to_string()).collect();
+    format!(
+        "No {:?} with index {} (known: {})",
+        target,
+        index,
+        if known.is_empty() {
+            "none".to_string()
+        } else {
+            known.join(", ")
+        }
+    )
+}
+
 async fn send_command(socket_path: &str, cmd: CliCommand) -> anyhow::Result<DaemonResponse> {
     let mut stream = UnixStream::connect(socket_path).await?;
     let cmd_json = serde_json::to_string(&cmd)?;
@@ -1000,6 +2018,22 @@ async fn send_command(socket_path: &str, cmd: CliCommand) -> anyhow::Result<Daem
 // ============================================================================
 // CLIENT CONNECTION HANDLER
 // ============================================================================
+/// Counts a socket connection as open for as long as it is alive
+struct ConnectedClient(Arc<AtomicUsize>);
+
+impl ConnectedClient {
+    fn new(count: &Arc<AtomicUsize>) -> Self {
+        count.fetch_add(1, Ordering::Relaxed);
+        ConnectedClient(count.clone())
+    }
+}
+
+impl Drop for ConnectedClient {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 async fn handle_client(
     stream: UnixStream,
     cmd_tx: std::sync::mpsc::Sender<ActorCommand>,
@@ -1008,26 +2042,81 @@ async fn handle_client(
     let mut reader = BufReader::new(reader);
     let mut buffer = String::new();
 
-    reader.read_line(&mut buffer).await?;
-    let cmd: CliCommand = serde_json::from_str(buffer.trim())?;
+    // One request per line until the client closes its end
+    loop {
+        buffer.clear();
+        if reader.read_line(&mut buffer).await? == 0 {
+            return Ok(());
+        }
+        // A malformed request gets an error reply; the connection stays open
+        let (id, cmd) = match serde_json::from_str(buffer.trim()) {
+            Ok(Request::Tagged { id, command }) => (Some(id), Ok(command)),
+            Ok(Request::Bare(command)) => (None, Ok(command)),
+            Err(e) => (request_id(buffer.trim()), Err(e)),
+        };
 
-    match cmd {
-        CliCommand::Kill => {
-            let resp = serde_json::to_string(&DaemonResponse::Success)?;
-            writer.write_all(resp.as_bytes()).await?;
-            writer.write_all(b"\n").await?;
+        let kill = matches!(cmd, Ok(CliCommand::Kill));
+        let response = match cmd {
+            Ok(CliCommand::Kill) => DaemonResponse::Success,
+            Ok(cmd) => run_client_command(cmd, &cmd_tx)?,
+            Err(e) => DaemonResponse::Error(format!("Invalid request: {}", e)),
+        };
+
+        let resp = match id {
+            Some(id) => serde_json::to_string(&Reply { id, response })?,
+            None => serde_json::to_string(&response)?,
+        };
+        writer.write_all(resp.as_bytes()).await?;
+        writer.write_all(b"\n").await?;
+
+        if kill {
             std::process::exit(0);
         }
-        CliCommand::GetState => {
+    }
+}
+
+/// The `id` of a request whose command didn't parse, if the envelope did
+fn request_id(line: &str) -> Option<serde_json::Value> {
+    match serde_json::from_str(line) {
+        Ok(serde_json::Value::Object(mut envelope)) => envelope.remove("id"),
+        _ => None,
+    }
+}
+
+/// Forward one client command to the actor and wait for its answer
+fn run_client_command(
+    cmd: CliCommand,
+    cmd_tx: &std::sync::mpsc::Sender<ActorCommand>,
+) -> anyhow::Result<DaemonResponse> {
+    let timeout = std::time::Duration::from_secs(1);
+    // Outcome of a command that only succeeds or fails
+    let outcome = |rx: std::sync::mpsc::Receiver<Result<(), String>>| match rx.recv_timeout(timeout)
+    {
+        Ok(Ok(_)) => DaemonResponse::Success,
+        Ok(Err(e)) => DaemonResponse::Error(e),
+        Err(_) => DaemonResponse::Error("Timeout".to_string()),
+    };
+
+    let response = match cmd {
+        CliCommand::GetState { .. } => {
             let (response_tx, response_rx) = std::sync::mpsc::channel();
             cmd_tx.send(ActorCommand::GetState(response_tx))?;
-            let state = response_rx
-                .recv_timeout(std::time::Duration::from_secs(1))
-                .unwrap_or_default();
+            let state = response_rx.recv_timeout(timeout).unwrap_or_default();
+            DaemonResponse::State(state)
+        }
+        CliCommand::Describe { target, index } => {
+            let (response_tx, response_rx) = std::sync::mpsc::channel();
+            cmd_tx.send(ActorCommand::Describe {
+                target,
+                index,
+                response: response_tx,
+            })?;
 
-            let resp = serde_json::to_string(&DaemonResponse::State(state))?;
-            writer.write_all(resp.as_bytes()).await?;
-            writer.write_all(b"\n").await?;
+            match response_rx.recv_timeout(timeout) {
+                Ok(Ok(info)) => DaemonResponse::Target(info),
+                Ok(Err(e)) => DaemonResponse::Error(e),
+                Err(_) => DaemonResponse::Error("Timeout".to_string()),
+            }
         }
         CliCommand::SetVolume {
             target,
@@ -1041,25 +2130,37 @@ async fn handle_client(
                 percent: volume,
                 response: response_tx,
             })?;
-
-            match response_rx.recv_timeout(std::time::Duration::from_secs(1)) {
-                Ok(Ok(_)) => {
-                    let resp = serde_json::to_string(&DaemonResponse::Success)?;
-                    writer.write_all(resp.as_bytes()).await?;
-                    writer.write_all(b"\n").await?;
-                }
-                Ok(Err(e)) => {
-                    let resp = serde_json::to_string(&DaemonResponse::Error(e))?;
-                    writer.write_all(resp.as_bytes()).await?;
-                    writer.write_all(b"\n").await?;
-                }
-                Err(_) => {
-                    let resp =
-                        serde_json::to_string(&DaemonResponse::Error("Timeout".to_string()))?;
-                    writer.write_all(resp.as_bytes()).await?;
-                    writer.write_all(b"\n").await?;
-                }
-            }
+            outcome(response_rx)
+        }
+        CliCommand::SetChannelVolume {
+            target,
+            index,
+            channel,
+            volume,
+        } => {
+            let (response_tx, response_rx) = std::sync::mpsc::channel();
+            cmd_tx.send(ActorCommand::SetChannelVolume {
+                target,
+                index,
+                channel,
+                percent: volume,
+                response: response_tx,
+            })?;
+            outcome(response_rx)
+        }
+        CliCommand::SetBalance {
+            target,
+            index,
+            balance,
+        } => {
+            let (response_tx, response_rx) = std::sync::mpsc::channel();
+            cmd_tx.send(ActorCommand::SetBalance {
+                target,
+                index,
+                balance,
+                response: response_tx,
+            })?;
+            outcome(response_rx)
         }
         CliCommand::ToggleMute { target, index } => {
             let (response_tx, response_rx) = std::sync::mpsc::channel();
@@ -1068,25 +2169,7 @@ async fn handle_client(
                 index,
                 response: response_tx,
             })?;
-
-            match response_rx.recv_timeout(std::time::Duration::from_secs(1)) {
-                Ok(Ok(_)) => {
-                    let resp = serde_json::to_string(&DaemonResponse::Success)?;
-                    writer.write_all(resp.as_bytes()).await?;
-                    writer.write_all(b"\n").await?;
-                }
-                Ok(Err(e)) => {
-                    let resp = serde_json::to_string(&DaemonResponse::Error(e))?;
-                    writer.write_all(resp.as_bytes()).await?;
-                    writer.write_all(b"\n").await?;
-                }
-                Err(_) => {
-                    let resp =
-                        serde_json::to_string(&DaemonResponse::Error("Timeout".to_string()))?;
-                    writer.write_all(resp.as_bytes()).await?;
-                    writer.write_all(b"\n").await?;
-                }
-            }
+            outcome(response_rx)
         }
         CliCommand::SetDefault { target, name } => {
             let (response_tx, response_rx) = std::sync::mpsc::channel();
@@ -1095,36 +2178,23 @@ async fn handle_client(
                 name,
                 response: response_tx,
             })?;
-
-            match response_rx.recv_timeout(std::time::Duration::from_secs(1)) {
-                Ok(Ok(_)) => {
-                    let resp = serde_json::to_string(&DaemonResponse::Success)?;
-                    writer.write_all(resp.as_bytes()).await?;
-                    writer.write_all(b"\n").await?;
-                }
-                Ok(Err(e)) => {
-                    let resp = serde_json::to_string(&DaemonResponse::Error(e))?;
-                    writer.write_all(resp.as_bytes()).await?;
-                    writer.write_all(b"\n").await?;
-                }
-                Err(_) => {
-                    let resp =
-                        serde_json::to_string(&DaemonResponse::Error("Timeout".to_string()))?;
-                    writer.write_all(resp.as_bytes()).await?;
-                    writer.write_all(b"\n").await?;
-                }
-            }
+            outcome(response_rx)
+        }
+        CliCommand::PushToTalk { press } => {
+            let (response_tx, response_rx) = std::sync::mpsc::channel();
+            cmd_tx.send(ActorCommand::PushToTalk {
+                press,
+                response: response_tx,
+            })?;
+            outcome(response_rx)
         }
-        CliCommand::Listen => {
-            let resp = serde_json::to_string(&DaemonResponse::Error(
-                "Listen command cannot be sent to daemon".to_string(),
-            ))?;
-            writer.write_all(resp.as_bytes()).await?;
-            writer.write_all(b"\n").await?;
+        CliCommand::Kill => DaemonResponse::Success,
+        CliCommand::Listen { .. } | CliCommand::Dump => {
+            DaemonResponse::Error("Listen and Dump commands cannot be sent to daemon".to_string())
         }
-    }
+    };
 
-    Ok(())
+    Ok(response)
 }
 // ============================================================================
 // MAIN ASYNC SERVER
@@ -1134,14 +2204,7 @@ async fn main() -> anyhow::Result<()> {
     let args = Cli::parse();
 
     match args.command {
-        CliCommand::Listen => {
-            if std::path::Path::new(&args.socket).exists() {
-                std::fs::remove_file(&args.socket)?;
-            }
-
-            let listener = UnixListener::bind(&args.socket)?;
-            eprintln!("EWW Mixer: Listening on {}", args.socket);
-
+        CliCommand::Listen { config } => {
             // Create channels
             let (broadcast_tx, broadcast_rx): (
                 std::sync::mpsc::Sender<MixerState>,
@@ -1154,25 +2217,51 @@ async fn main() -> anyhow::Result<()> {
             ) = std::sync::mpsc::channel();
 
             let cmd_tx_clone = cmd_tx.clone();
+            let compat = config.compat;
+            let connected_clients = Arc::new(AtomicUsize::new(0));
+            let actor_clients = connected_clients.clone();
 
             // Spawn actor thread
             let _actor_handle = std::thread::spawn(move || {
-                let mut actor = PulseAudioActor::new().expect("Failed to create PulseAudio actor");
-                // Set the broadcast channel
-                actor.broadcast_tx = Some(broadcast_tx);
-                actor.run_actor_loop(cmd_rx);
+                let backend = match PulseAudioActor::new(config.clone()) {
+                    Ok(actor) => Some(AudioBackend::PulseAudio(Box::new(actor))),
+                    #[cfg(feature = "pipewire")]
+                    Err(e) if pipewire::is_available() => {
+                        eprintln!("PulseAudio unavailable ({}), using PipeWire backend", e);
+                        Some(AudioBackend::PipeWire(pipewire::PipeWireActor::new(config)))
+                    }
+                    Err(e) => {
+                        eprintln!("PulseAudio unavailable ({}), retrying in background", e);
+                        wait_for_audio_server(&config, &cmd_rx)
+                    }
+                };
+
+                // Set the broadcast channel; the first refresh broadcasts the upgrade
+                match backend {
+                    Some(AudioBackend::PulseAudio(mut actor)) => {
+                        actor.broadcast_tx = Some(broadcast_tx);
+                        actor.connected_clients = actor_clients;
+                        actor.run_actor_loop(cmd_rx);
+                    }
+                    #[cfg(feature = "pipewire")]
+                    Some(AudioBackend::PipeWire(mut actor)) => {
+                        actor.broadcast_tx = Some(broadcast_tx);
+                        actor.run_actor_loop(cmd_rx);
+                    }
+                    None => {}
+                }
             });
 
-            // Wait for actor to initialize
-            std::thread::sleep(std::time::Duration::from_millis(300));
-
-            // Get and print initial state
+            // Get and print initial state; the actor answers once its first
+            // enumeration is done, so this also waits for it to initialize
             let (response_tx, response_rx) = std::sync::mpsc::channel();
             cmd_tx.send(ActorCommand::GetState(response_tx))?;
 
-            match response_rx.recv_timeout(std::time::Duration::from_secs(2)) {
+            match response_rx
+                .recv_timeout(std::time::Duration::from_millis(INITIAL_STATE_TIMEOUT_MS))
+            {
                 Ok(initial_state) => {
-                    println!("{}", serde_json::to_string(&initial_state)?);
+                    println!("{}", compat::to_json(&initial_state, compat)?);
                 }
                 Err(e) => {
                     eprintln!("Failed to get initial state: {}", e);
@@ -1180,10 +2269,18 @@ async fn main() -> anyhow::Result<()> {
                 }
             }
 
+            // Only accept clients once there is a complete state to serve them
+            if std::path::Path::new(&args.socket).exists() {
+                std::fs::remove_file(&args.socket)?;
+            }
+
+            let listener = UnixListener::bind(&args.socket)?;
+            eprintln!("EWW Mixer: Listening on {}", args.socket);
+
             // Spawn stdout printer for state updates
             tokio::task::spawn_blocking(move || {
                 while let Ok(state) = broadcast_rx.recv() {
-                    println!("{}", serde_json::to_string(&state).unwrap_or_default());
+                    println!("{}", compat::to_json(&state, compat).unwrap_or_default());
                 }
             });
 
@@ -1191,30 +2288,307 @@ async fn main() -> anyhow::Result<()> {
             loop {
                 let (stream, _) = listener.accept().await?;
                 let cmd_tx_clone = cmd_tx_clone.clone();
+                let client = ConnectedClient::new(&connected_clients);
 
                 tokio::spawn(async move {
+                    let _client = client;
                     if let Err(e) = handle_client(stream, cmd_tx_clone).await {
                         eprintln!("Client error: {}", e);
                     }
                 });
             }
         }
-        cmd => match send_command(&args.socket, cmd).await {
-            Ok(DaemonResponse::Success) => {}
-            Ok(DaemonResponse::State(state)) => {
-                println!("{}", serde_json::to_string(&state)?);
+        CliCommand::Dump => {
+            // Transient actor: one snapshot, then the connection is dropped
+            let mut actor = PulseAudioActor::new(DaemonConfig::default())?;
+            println!("{}", serde_json::to_string_pretty(&actor.get_state())?);
+        }
+        cmd => {
+            let (exit_on_mute, check_mic, compat) = match cmd {
+                CliCommand::GetState {
+                    exit_on_mute,
+                    check_mic,
+                    compat,
+                } => (exit_on_mute, check_mic, compat),
+                _ => (false, false, None),
+            };
+
+            match send_command(&args.socket, cmd).await {
+                Ok(DaemonResponse::Success) => {}
+                Ok(DaemonResponse::State(state)) => {
+                    println!("{}", compat::to_json(&state, compat)?);
+
+                    // Let keybind scripts branch on mute without parsing JSON
+                    if exit_on_mute && state.volume_muted {
+                        std::process::exit(EXIT_SINK_MUTED);
+                    }
+                    if check_mic && state.mic_muted {
+                        std::process::exit(EXIT_SOURCE_MUTED);
+                    }
+                }
+                Ok(DaemonResponse::Target(info)) => {
+                    println!("{}", serde_json::to_string(&info)?);
+                }
+                Ok(DaemonResponse::Error(e)) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(EXIT_DAEMON_ERROR);
+                }
+                Err(e) => {
+                    eprintln!("Failed to connect to daemon: {}", e);
+                    eprintln!("Make sure the daemon is running: eww-mixer listen");
+                    std::process::exit(EXIT_NO_DAEMON);
+                }
             }
-            Ok(DaemonResponse::Error(e)) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> DaemonConfig {
+        DaemonConfig {
+            max_inputs: None,
+            update_interval_ms: STATE_UPDATE_INTERVAL_MS,
+            meter_interval_ms: None,
+            ignore_apps: Vec::new(),
+            sort: DeviceSort::Default,
+            monitor_sink: None,
+            volume_curve: VolumeCurve::Linear,
+            meter_idle_timeout: None,
+            compat: None,
+        }
+    }
+
+    fn sink(index: u32, name: &str, volume: u8) -> SinkInfo {
+        SinkInfo {
+            index,
+            name: name.to_string(),
+            volume,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn default_sink_listed_second_is_the_only_one_flagged() {
+        // Mock backend listing: the default sink is enumerated second
+        let mut state = MixerState {
+            sinks: vec![
+                sink(1, "alsa_output.hdmi", 30),
+                sink(2, "alsa_output.analog", 70),
+                sink(3, "bluez_output.headset", 50),
+            ],
+            ..Default::default()
+        };
+        let defaults = DefaultNames {
+            sink: Some("alsa_output.analog".to_string()),
+            ..Default::default()
+        };
+
+        finalize_state(&mut state, &config(), &defaults);
+
+        let flagged: Vec<_> = state.sinks.iter().filter(|s| s.is_default).collect();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].index, 2);
+        assert_eq!(state.sinks[0].index, 2);
+        assert_eq!(state.volume_percent, 70);
+        assert!(state.default_sink_resolved);
+    }
+
+    #[test]
+    fn stereo_peaks_are_computed_per_channel() {
+        // Interleaved frames: loud left channel, silent right channel
+        let data: Vec<u8> = (0..64)
+            .flat_map(|_| {
+                let mut frame = 12000i16.to_le_bytes().to_vec();
+                frame.extend_from_slice(&0i16.to_le_bytes());
+                frame
+            })
+            .collect();
+
+        let [left, right] = PulseAudioActor::calculate_peak_volume::<2>(&data);
+        assert_eq!(left, 100);
+        assert_eq!(right, 0);
+
+        let [mono] = PulseAudioActor::calculate_peak_volume::<1>(&data);
+        assert!(mono > 0 && mono < left);
+    }
+
+    #[test]
+    fn renamed_default_sink_is_matched_by_description() {
+        let mut renamed = sink(4, "alsa_output.analog.2", 40);
+        renamed.description = "Built-in Audio".to_string();
+        let mut state = MixerState {
+            sinks: vec![sink(1, "alsa_output.hdmi", 30), renamed],
+            ..Default::default()
+        };
+        let defaults = DefaultNames {
+            sink: Some("alsa_output.analog".to_string()),
+            sink_description: Some("Built-in Audio".to_string()),
+            ..Default::default()
+        };
+
+        finalize_state(&mut state, &config(), &defaults);
+
+        assert!(state.sinks[0].is_default);
+        assert_eq!(state.sinks[0].index, 4);
+        assert!(!state.sinks[1].is_default);
+        assert!(state.default_sink_resolved);
+    }
+
+    #[test]
+    fn volume_curves_round_trip_every_percent() {
+        // 1% cubed is below the smallest raw PulseAudio volume step
+        for curve in [VolumeCurve::Linear, VolumeCurve::Cubic] {
+            for percent in (0..=100).filter(|&p| p != 1 || curve == VolumeCurve::Linear) {
+                let volume = curve.to_volume(percent);
+                assert_eq!(curve.volume_percent(volume), percent, "{:?}", curve);
             }
-            Err(e) => {
-                eprintln!("Failed to connect to daemon: {}", e);
-                eprintln!("Make sure the daemon is running: eww-mixer listen");
-                std::process::exit(1);
+        }
+        assert_eq!(VolumeCurve::Cubic.to_volume(50).0, Volume::NORMAL.0 / 8);
+    }
+
+    #[test]
+    fn missing_target_lists_known_indices() {
+        let state = MixerState {
+            sinks: vec![
+                sink(1, "alsa_output.hdmi", 30),
+                sink(4, "alsa_output.analog", 40),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            target_not_found(&state, AudioTarget::Sink, 2),
+            "No Sink with index 2 (known: 1, 4)"
+        );
+        assert_eq!(
+            target_not_found(&state, AudioTarget::SinkInput, 4),
+            "No SinkInput with index 4 (known: none)"
+        );
+    }
+
+    #[test]
+    fn default_index_resolves_only_devices() {
+        let mut state = MixerState {
+            sinks: vec![
+                sink(1, "alsa_output.hdmi", 30),
+                sink(4, "alsa_output.analog", 40),
+            ],
+            ..Default::default()
+        };
+        let defaults = DefaultNames {
+            sink: Some("alsa_output.analog".to_string()),
+            ..Default::default()
+        };
+        finalize_state(&mut state, &config(), &defaults);
+
+        assert_eq!(default_index(&state, AudioTarget::Sink), Ok(4));
+        assert!(default_index(&state, AudioTarget::Source).is_err());
+        assert!(default_index(&state, AudioTarget::SinkInput).is_err());
+        assert_eq!(parse_index("default"), Ok(DEFAULT_INDEX));
+        assert_eq!(parse_index("7"), Ok(7));
+    }
+
+    #[test]
+    fn requests_parse_with_and_without_id() {
+        let tagged: Request =
+            serde_json::from_str(r#"{"id":7,"command":{"PushToTalk":{"press":true}}}"#).unwrap();
+        assert!(matches!(
+            tagged,
+            Request::Tagged { id, command: CliCommand::PushToTalk { press: true } } if id == 7
+        ));
+
+        let bare: Request = serde_json::from_str(r#""Kill""#).unwrap();
+        assert!(matches!(bare, Request::Bare(CliCommand::Kill)));
+
+        let malformed = r#"{"id":8,"command":"Louder"}"#;
+        assert!(serde_json::from_str::<Request>(malformed).is_err());
+        assert_eq!(request_id(malformed), Some(serde_json::json!(8)));
+        assert_eq!(request_id("not json"), None);
+
+        let reply = Reply {
+            id: serde_json::json!("a"),
+            response: DaemonResponse::Success,
+        };
+        assert_eq!(
+            serde_json::to_string(&reply).unwrap(),
+            r#"{"id":"a","response":"Success"}"#
+        );
+    }
+
+    #[test]
+    fn microphone_compat_names_the_recorded_source() {
+        let state = MixerState {
+            mic_percent: 60,
+            sources: vec![SourceInfo {
+                index: 2,
+                name: "alsa_input.usb".to_string(),
+                description: "USB Mic".to_string(),
+                channel_volumes: vec![60],
+                ..Default::default()
+            }],
+            source_outputs: vec![SourceOutputInfo {
+                index: 9,
+                name: "OBS".to_string(),
+                source_index: 2,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let json: serde_json::Value =
+            serde_json::from_str(&compat::to_json(&state, Some(Compat::Microphone)).unwrap())
+                .unwrap();
+
+        assert_eq!(json["percent"], 60);
+        assert_eq!(json["source_outputs"][0]["source_name"], "USB Mic");
+        assert!(json["sources"][0].get("channel_volumes").is_none());
+        assert!(json.get("sinks").is_none());
+    }
+
+    #[test]
+    fn set_balance_takes_negative_values_within_range() {
+        let cli =
+            Cli::try_parse_from(["eww-mixer", "set-balance", "sink-input", "4", "-40"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            CliCommand::SetBalance {
+                target: AudioTarget::SinkInput,
+                index: 4,
+                balance: -40,
             }
-        },
+        ));
+
+        assert!(
+            Cli::try_parse_from(["eww-mixer", "set-balance", "sink", "default", "101"]).is_err()
+        );
     }
 
-    Ok(())
+    #[test]
+    fn volume_compat_leaves_out_balance() {
+        let state = MixerState {
+            sinks: vec![SinkInfo {
+                balance: 30,
+                ..sink(1, "alsa_output.analog", 50)
+            }],
+            sink_inputs: vec![SinkInputInfo {
+                index: 5,
+                name: "mpv".to_string(),
+                balance: -20,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let json: serde_json::Value =
+            serde_json::from_str(&compat::to_json(&state, Some(Compat::Volume)).unwrap()).unwrap();
+
+        assert_eq!(json["sink_inputs"][0]["name"], "mpv");
+        assert!(json["sink_inputs"][0].get("balance").is_none());
+        assert!(json["sinks"][0].get("balance").is_none());
+    }
 }
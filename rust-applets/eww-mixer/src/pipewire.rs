@@ -0,0 +1,468 @@
+// ============================================================================
+// PIPEWIRE NATIVE BACKEND (feature = "pipewire")
+// ============================================================================
+//
+// Used when the libpulse connection fails, e.g. on PipeWire setups without
+// pipewire-pulse. Follows the graph with one long-running `pw-dump --monitor`
+// and drives it with `wpctl`, so no libpipewire headers are needed at build time.
+//
+// - Node ids are reported as `index` for every target type
+// - Volumes use wpctl's cubic scale, matching what pactl reports, before
+//   --volume-curve is applied
+// - Peak levels are not metered (always 0)
+// - Channel volumes, labels and balance are not reported
+//
+// ============================================================================
+
+use crate::{
+    default_index, finalize_state, target_not_found, ActorCommand, AudioTarget, DaemonConfig,
+    DefaultNames, DefaultTarget, MixerState, SinkInfo, SinkInputInfo, SourceInfo, SourceOutputInfo,
+    TargetInfo, VolumeCurve, DEFAULT_INDEX,
+};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufReader;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+// Delay before restarting `pw-dump --monitor` after it exits
+const MONITOR_RESTART_MS: u64 = 1000;
+// How long the actor waits for the first dump before answering commands
+const FIRST_DUMP_TIMEOUT_MS: u64 = 2000;
+
+/// PipeWire objects by id, as last reported by `pw-dump --monitor`
+type Graph = Arc<Mutex<BTreeMap<u64, Value>>>;
+
+/// Returns true when the PipeWire CLI tools can reach a running daemon
+pub fn is_available() -> bool {
+    Command::new("wpctl")
+        .arg("status")
+        .output()
+        .map(|out| out.status.success())
+        .unwrap_or(false)
+}
+
+pub struct PipeWireActor {
+    config: DaemonConfig,
+    graph: Graph,
+    ptt_saved: Option<(u32, bool)>,
+    pub broadcast_tx: Option<std::sync::mpsc::Sender<MixerState>>,
+}
+
+impl PipeWireActor {
+    pub fn new(config: DaemonConfig) -> Self {
+        Self {
+            config,
+            graph: Graph::default(),
+            ptt_saved: None,
+            broadcast_tx: None,
+        }
+    }
+
+    /// Get complete mixer state from the monitored graph
+    fn get_state(&self) -> MixerState {
+        build_state(&graph_objects(&self.graph), &self.config)
+    }
+
+    /// Look up one target, including streams hidden by --ignore-app or --max-inputs
+    fn describe(&self, target: AudioTarget, index: u32) -> Result<TargetInfo, String> {
+        let unfiltered = DaemonConfig {
+            max_inputs: None,
+            ignore_apps: Vec::new(),
+            ..self.config.clone()
+        };
+        let state = build_state(&graph_objects(&self.graph), &unfiltered);
+        let index = match index {
+            DEFAULT_INDEX => default_index(&state, target)?,
+            index => index,
+        };
+
+        let found = match target {
+            AudioTarget::Sink => state
+                .sinks
+                .iter()
+                .find(|s| s.index == index)
+                .cloned()
+                .map(TargetInfo::Sink),
+            AudioTarget::SinkInput => state
+                .sink_inputs
+                .iter()
+                .find(|s| s.index == index)
+                .cloned()
+                .map(TargetInfo::SinkInput),
+            AudioTarget::Source => state
+                .sources
+                .iter()
+                .find(|s| s.index == index)
+                .cloned()
+                .map(TargetInfo::Source),
+            AudioTarget::SourceOutput => state
+                .source_outputs
+                .iter()
+                .find(|s| s.index == index)
+                .cloned()
+                .map(TargetInfo::SourceOutput),
+        };
+        found.ok_or_else(|| target_not_found(&state, target, index))
+    }
+
+    /// Set volume for any audio target (node id)
+    fn set_volume(&self, target: AudioTarget, index: u32, percent: u8) -> Result<(), String> {
+        let level = format!("{:.4}", self.config.volume_curve.fraction(percent.min(100)));
+        wpctl(&["set-volume", &node_arg(target, index)?, &level])
+            .map_err(|e| format!("Failed to set volume for {:?} {}: {}", target, index, e))
+    }
+
+    /// Toggle mute for any audio target (node id)
+    fn toggle_mute(&self, target: AudioTarget, index: u32) -> Result<(), String> {
+        wpctl(&["set-mute", &node_arg(target, index)?, "toggle"])
+            .map_err(|e| format!("Failed to toggle mute for {:?} {}: {}", target, index, e))
+    }
+
+    /// Set default device by node name
+    fn set_default(&self, target: DefaultTarget, name: &str) -> Result<(), String> {
+        let state = self.get_state();
+        let id = match target {
+            DefaultTarget::Sink => state.sinks.iter().find(|s| s.name == name).map(|s| s.index),
+            DefaultTarget::Source => state
+                .sources
+                .iter()
+                .find(|s| s.name == name)
+                .map(|s| s.index),
+        };
+
+        let Some(id) = id else {
+            return Err(format!("Failed to set default {:?} to {}", target, name));
+        };
+        wpctl(&["set-default", &id.to_string()])
+            .map_err(|e| format!("Failed to set default {:?} to {}: {}", target, name, e))
+    }
+
+    /// Push-to-talk on the default source, same semantics as the PulseAudio actor
+    fn push_to_talk(&mut self, press: bool) -> Result<(), String> {
+        if !press {
+            let Some((id, was_muted)) = self.ptt_saved.take() else {
+                return Ok(());
+            };
+            return set_mute(id, was_muted);
+        }
+
+        let (id, _) = match self.ptt_saved {
+            Some(saved) => saved,
+            None => {
+                let state = self.get_state();
+                let source = state
+                    .sources
+                    .iter()
+                    .find(|s| s.is_default)
+                    .ok_or_else(|| "No default source for push-to-talk".to_string())?;
+                *self.ptt_saved.insert((source.index, source.muted))
+            }
+        };
+        set_mute(id, false)
+    }
+
+    /// Main actor loop - same command surface as the PulseAudio actor. State
+    /// changes are broadcast by the graph monitor, not after each command.
+    pub fn run_actor_loop(mut self, rx: std::sync::mpsc::Receiver<ActorCommand>) {
+        let graph = self.graph.clone();
+        let config = self.config.clone();
+        let broadcast_tx = self.broadcast_tx.clone();
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || monitor_graph(&graph, &config, broadcast_tx, ready_tx));
+        let _ = ready_rx.recv_timeout(std::time::Duration::from_millis(FIRST_DUMP_TIMEOUT_MS));
+
+        for cmd in rx {
+            match cmd {
+                ActorCommand::GetState(response) => {
+                    let _ = response.send(self.get_state());
+                }
+                ActorCommand::SetVolume {
+                    target,
+                    index,
+                    percent,
+                    response,
+                } => {
+                    let _ = response.send(self.set_volume(target, index, percent));
+                }
+                ActorCommand::SetChannelVolume { response, .. } => {
+                    let _ = response.send(Err(
+                        "Per-channel volume is not supported by the PipeWire backend".to_string(),
+                    ));
+                }
+                ActorCommand::SetBalance { response, .. } => {
+                    let _ = response.send(Err(
+                        "Balance is not supported by the PipeWire backend".to_string()
+                    ));
+                }
+                ActorCommand::ToggleMute {
+                    target,
+                    index,
+                    response,
+                } => {
+                    let _ = response.send(self.toggle_mute(target, index));
+                }
+                ActorCommand::SetDefault {
+                    target,
+                    name,
+                    response,
+                } => {
+                    let _ = response.send(self.set_default(target, &name));
+                }
+                ActorCommand::PushToTalk { press, response } => {
+                    let _ = response.send(self.push_to_talk(press));
+                }
+                ActorCommand::Describe {
+                    target,
+                    index,
+                    response,
+                } => {
+                    let _ = response.send(self.describe(target, index));
+                }
+            }
+        }
+    }
+}
+
+/// Mixer state for a set of PipeWire objects, filtered and sorted as `config` asks
+fn build_state(objects: &[Value], config: &DaemonConfig) -> MixerState {
+    let mut state = MixerState::default();
+    let defaults = default_device_names(objects);
+
+    // Stream node id -> device node id, taken from the link graph
+    let mut links: HashMap<u64, u64> = HashMap::new();
+    for obj in objects {
+        if obj["type"] == "PipeWire:Interface:Link" {
+            let info = &obj["info"];
+            if let (Some(out), Some(inp)) = (
+                info["output-node-id"].as_u64(),
+                info["input-node-id"].as_u64(),
+            ) {
+                links.entry(out).or_insert(inp);
+                links.entry(inp).or_insert(out);
+            }
+        }
+    }
+
+    for obj in objects {
+        if obj["type"] != "PipeWire:Interface:Node" {
+            continue;
+        }
+        let Some(id) = obj["id"].as_u64() else {
+            continue;
+        };
+        let props = &obj["info"]["props"];
+        let (volume, muted) = node_volume(obj, config.volume_curve);
+        let index = id as u32;
+        let linked = links.get(&id).copied().unwrap_or_default() as u32;
+
+        match props["media.class"].as_str().unwrap_or_default() {
+            "Audio/Sink" => state.sinks.push(SinkInfo {
+                index,
+                name: prop_str(props, "node.name"),
+                description: prop_str(props, "node.description"),
+                volume,
+                muted,
+                ..Default::default()
+            }),
+            "Audio/Source" => state.sources.push(SourceInfo {
+                index,
+                name: prop_str(props, "node.name"),
+                description: prop_str(props, "node.description"),
+                volume,
+                muted,
+                ..Default::default()
+            }),
+            "Stream/Output/Audio" => state.sink_inputs.push(SinkInputInfo {
+                index,
+                name: app_name(props),
+                volume,
+                muted,
+                sink_index: linked,
+                ..Default::default()
+            }),
+            "Stream/Input/Audio" => state.source_outputs.push(SourceOutputInfo {
+                index,
+                name: app_name(props),
+                volume,
+                muted,
+                source_index: linked,
+                ..Default::default()
+            }),
+            _ => {}
+        }
+    }
+
+    finalize_state(&mut state, config, &defaults);
+    state
+}
+
+/// Keep `graph` in step with `pw-dump --monitor`, broadcasting every state
+/// change. pw-dump is restarted if it exits, e.g. when PipeWire restarts.
+/// `ready` is signalled once the first full dump is in the graph.
+fn monitor_graph(
+    graph: &Graph,
+    config: &DaemonConfig,
+    broadcast_tx: Option<std::sync::mpsc::Sender<MixerState>>,
+    ready: std::sync::mpsc::Sender<()>,
+) {
+    let mut last_state = MixerState::default();
+
+    loop {
+        match Command::new("pw-dump")
+            .arg("--monitor")
+            .stdout(Stdio::piped())
+            .spawn()
+        {
+            Ok(mut child) => {
+                if let Some(stdout) = child.stdout.take() {
+                    // The first update is the whole graph, later ones only what changed
+                    let updates = serde_json::Deserializer::from_reader(BufReader::new(stdout))
+                        .into_iter::<Vec<Value>>();
+                    for update in updates {
+                        let update = match update {
+                            Ok(update) => update,
+                            Err(e) => {
+                                eprintln!("pw-dump --monitor: {}", e);
+                                break;
+                            }
+                        };
+                        apply_update(&mut graph.lock().unwrap(), update);
+                        let _ = ready.send(());
+
+                        let state = build_state(&graph_objects(graph), config);
+                        if state != last_state {
+                            if let Some(tx) = &broadcast_tx {
+                                if tx.send(state.clone()).is_err() {
+                                    return;
+                                }
+                            }
+                            last_state = state;
+                        }
+                    }
+                }
+                let _ = child.kill();
+                let _ = child.wait();
+                graph.lock().unwrap().clear();
+            }
+            Err(e) => eprintln!("pw-dump failed: {}", e),
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(MONITOR_RESTART_MS));
+    }
+}
+
+/// Merge one `pw-dump --monitor` update: changed objects are printed whole,
+/// removed ones as just their id with a null `info` (or `props`)
+fn apply_update(graph: &mut BTreeMap<u64, Value>, update: Vec<Value>) {
+    for obj in update {
+        let Some(id) = obj["id"].as_u64() else {
+            continue;
+        };
+        let removed = ["info", "props"]
+            .iter()
+            .any(|key| obj.get(key).is_some_and(Value::is_null));
+        if removed {
+            graph.remove(&id);
+        } else {
+            graph.insert(id, obj);
+        }
+    }
+}
+
+/// A snapshot of the monitored objects, in id order
+fn graph_objects(graph: &Graph) -> Vec<Value> {
+    graph.lock().unwrap().values().cloned().collect()
+}
+
+fn wpctl(args: &[&str]) -> Result<(), String> {
+    let output = Command::new("wpctl")
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+fn set_mute(id: u32, mute: bool) -> Result<(), String> {
+    let value = if mute { "1" } else { "0" };
+    wpctl(&["set-mute", &id.to_string(), value])
+        .map_err(|e| format!("Failed to set mute for node {}: {}", id, e))
+}
+
+/// Default sink/source node names from the "default" metadata object
+fn default_device_names(objects: &[Value]) -> DefaultNames {
+    let mut defaults = DefaultNames::default();
+
+    for obj in objects {
+        if obj["type"] != "PipeWire:Interface:Metadata"
+            || obj["props"]["metadata.name"] != "default"
+        {
+            continue;
+        }
+        for entry in obj["metadata"].as_array().into_iter().flatten() {
+            let name = entry["value"]["name"].as_str().map(|s| s.to_string());
+            match entry["key"].as_str() {
+                Some("default.audio.sink") => defaults.sink = name,
+                Some("default.audio.source") => defaults.source = name,
+                _ => {}
+            }
+        }
+    }
+
+    defaults
+}
+
+/// wpctl node argument; DEFAULT_INDEX maps to wpctl's own default aliases
+fn node_arg(target: AudioTarget, index: u32) -> Result<String, String> {
+    if index != DEFAULT_INDEX {
+        return Ok(index.to_string());
+    }
+    match target {
+        AudioTarget::Sink => Ok("@DEFAULT_AUDIO_SINK@".to_string()),
+        AudioTarget::Source => Ok("@DEFAULT_AUDIO_SOURCE@".to_string()),
+        AudioTarget::SinkInput | AudioTarget::SourceOutput => {
+            Err(format!("{:?} needs a real index, not \"default\"", target))
+        }
+    }
+}
+
+/// Volume percent (wpctl's cubic scale, then `curve`) and mute flag of a node
+fn node_volume(node: &Value, curve: VolumeCurve) -> (u8, bool) {
+    let Some(props) = node["info"]["params"]["Props"]
+        .as_array()
+        .and_then(|p| p.iter().find(|p| p.get("channelVolumes").is_some()))
+    else {
+        return (0, false);
+    };
+
+    let channels: Vec<f64> = props["channelVolumes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_f64())
+        .collect();
+    let linear = if channels.is_empty() {
+        0.0
+    } else {
+        channels.iter().sum::<f64>() / channels.len() as f64
+    };
+
+    let percent = curve.percent(linear.cbrt());
+    (percent, props["mute"].as_bool().unwrap_or(false))
+}
+
+fn prop_str(props: &Value, key: &str) -> String {
+    props[key].as_str().unwrap_or_default().to_string()
+}
+
+fn app_name(props: &Value) -> String {
+    props["application.name"]
+        .as_str()
+        .or_else(|| props["node.name"].as_str())
+        .unwrap_or("Unknown")
+        .to_string()
+}
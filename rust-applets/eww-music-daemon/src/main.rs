@@ -1,57 +1,127 @@
-use clap::{Parser, Subcommand};
-use mpris::{LoopStatus, PlaybackStatus, PlayerFinder};
+use clap::{Parser, Subcommand, ValueEnum};
+use mpris::{FindingError, LoopStatus, PlaybackStatus, Player, PlayerFinder};
 use serde::Serialize;
 use std::collections::HashMap;
 use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::io::Write;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
 const SWITCH_FILE: &str = "/tmp/eww-music-player-switch";
+const MIN_PLAYBACK_RATE: f64 = 0.25;
+const MAX_PLAYBACK_RATE: f64 = 4.0;
+const POLL_INTERVAL_MS: u64 = 500;
+const INTERPOLATE_TICK_MS: u64 = 100;
+const ART_TOUCH_INTERVAL_SECS: u64 = 3600; // How stale a cover's mtime gets before a hit refreshes it
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Hide players whose identity contains this text (case-insensitive, repeatable)
+    #[arg(long, global = true)]
+    ignore: Vec<String>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    Listen,
+    Listen {
+        /// Cover shown when a track has no art (default: generated from the artist name)
+        #[arg(long)]
+        default_art: Option<PathBuf>,
+        /// Advance the position locally between polls for a smoother seek bar
+        #[arg(long)]
+        interpolate: bool,
+        /// Cut titles longer than this many characters, ending in "…" (0 = never)
+        #[arg(long, default_value_t = 0)]
+        max_title: usize,
+        /// Cut artists longer than this many characters, ending in "…" (0 = never)
+        #[arg(long, default_value_t = 0)]
+        max_artist: usize,
+        /// Delete the least recently used covers once the cache grows past this (0 = never)
+        #[arg(long, default_value_t = 100)]
+        cache_max_mb: u64,
+    },
     PlayPause,
     Previous,
     Next,
-    Seek { percent: f64 },
-    Switch { bus_name: String },
-    Cycle { direction: String },
-    Volume { action: String },
+    Seek {
+        percent: f64,
+    },
+    Switch {
+        bus_name: String,
+    },
+    /// Activate the player whose track title contains this text (case-insensitive)
+    SwitchToPlaying {
+        query: String,
+    },
+    Cycle {
+        direction: String,
+    },
+    Volume {
+        action: String,
+    },
     Shuffle,
     Loop,
-    PlaybackRate { rate: f64 },
+    PlaybackRate {
+        rate: f64,
+        /// How to read `rate`: percent (150), decimal (1.5), or guess from its size
+        #[arg(long, value_enum, default_value_t = RateFormat::Auto)]
+        rate_format: RateFormat,
+    },
+    PlaybackRateReset,
+    PlaybackRateStep {
+        #[arg(allow_negative_numbers = true)]
+        delta: f64,
+    },
+    /// Print the active player's track list as JSON (empty if unsupported)
+    Tracklist,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum RateFormat {
+    /// Percent when above the maximum rate, decimal otherwise
+    Auto,
+    Percent,
+    Decimal,
 }
 
 #[derive(Debug, Clone, Serialize)]
 struct EwwMusicState {
+    // False only when no players exist; active_player then reads "No Player"
+    has_player: bool,
     active_player: String,
     active_bus: String,
     available_players: Vec<String>,
+    // Changes only when players appear or disappear; empty with no players
+    players_hash: String,
     next_player: String,
     prev_player: String,
     title: String,
     artist: String,
+    // Untruncated title/artist, e.g. for tooltips
+    title_full: String,
+    artist_full: String,
     album: String,
     art_url: String,
     playing: bool,
     status: String,
+    position_secs: f64,
+    length_secs: f64,
     position_percent: f64,
     position_time: String,
     duration_time: String,
     volume: f64,
+    volume_percent: u8, // `volume` as 0-100, matching the mixers
     can_play: bool,
     can_pause: bool,
     can_go_next: bool,
     can_go_previous: bool,
     can_seek: bool,
+    can_control: bool, // False for players that ignore every command
     shuffle: bool,
     loop_status: String,
     playback_rate: f64,
@@ -60,26 +130,34 @@ struct EwwMusicState {
 impl Default for EwwMusicState {
     fn default() -> Self {
         EwwMusicState {
+            has_player: false,
             active_player: "No Player".to_string(),
             active_bus: String::new(),
             available_players: vec![],
+            players_hash: String::new(),
             next_player: String::new(),
             prev_player: String::new(),
             title: "".to_string(),
             artist: "".to_string(),
+            title_full: String::new(),
+            artist_full: String::new(),
             album: "".to_string(),
             art_url: "".to_string(),
             playing: false,
             status: "Stopped".to_string(),
+            position_secs: 0.0,
+            length_secs: 0.0,
             position_percent: 0.0,
             position_time: "0:00".to_string(),
             duration_time: "0:00".to_string(),
             volume: 0.0,
+            volume_percent: 0,
             can_play: false,
             can_pause: false,
             can_go_next: false,
             can_go_previous: false,
             can_seek: false,
+            can_control: false,
             shuffle: false,
             loop_status: "None".to_string(),
             playback_rate: 1.0,
@@ -87,10 +165,23 @@ impl Default for EwwMusicState {
     }
 }
 
+/// Printed by every action command so callers can detect failures
+#[derive(Debug, Clone, Serialize)]
+struct ActionResult {
+    ok: bool,
+    error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TrackEntry {
+    id: String,
+    title: String,
+    artist: String,
+}
+
 fn log_to_file(msg: &str) {
     if let Ok(mut file) = fs::OpenOptions::new()
         .create(true)
-        .write(true)
         .append(true)
         .open("/tmp/eww-volume-debug.log")
     {
@@ -104,7 +195,87 @@ fn format_time(secs: u64) -> String {
     format!("{}:{:02}", m, s)
 }
 
-fn cache_album_art(url: &str) -> Option<String> {
+/// Marks a cover as used for eviction. Only the mtime is bumped, since most
+/// systems mount with relatime; a stale check keeps polls from writing each time.
+fn touch_cover(path: &Path) {
+    let Ok(file) = fs::File::options().append(true).open(path) else {
+        return;
+    };
+    let stale = file
+        .metadata()
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_none_or(|age| age.as_secs() >= ART_TOUCH_INTERVAL_SECS);
+    if stale {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// Deletes the least recently used covers until the cache fits in `max_bytes`,
+/// always keeping `keep` (the cover just written)
+fn evict_covers(cache_dir: &Path, max_bytes: u64, keep: &Path) {
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return;
+    };
+    let mut covers: Vec<(SystemTime, u64, PathBuf)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let meta = entry.metadata().ok()?;
+            if !meta.is_file() {
+                return None;
+            }
+            Some((meta.modified().ok()?, meta.len(), entry.path()))
+        })
+        .collect();
+
+    let mut total: u64 = covers.iter().map(|(_, len, _)| len).sum();
+    if total <= max_bytes {
+        return;
+    }
+    covers.sort();
+    for (_, len, path) in covers {
+        if total <= max_bytes {
+            break;
+        }
+        if path != keep && fs::remove_file(&path).is_ok() {
+            total = total.saturating_sub(len);
+        }
+    }
+}
+
+/// Checks a download is an image that wasn't cut short: known magic bytes, and
+/// for JPEG and PNG (most covers) the end marker as well
+fn is_complete_image(bytes: &[u8]) -> bool {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        // Some encoders pad after the end-of-image marker
+        let tail = &bytes[bytes.len().saturating_sub(32)..];
+        return tail.windows(2).any(|w| w == [0xFF, 0xD9]);
+    }
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let tail = &bytes[bytes.len().saturating_sub(32)..];
+        return tail.windows(4).any(|w| w == b"IEND");
+    }
+    bytes.starts_with(b"GIF8")
+        || (bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP"))
+        || bytes.starts_with(b"BM")
+}
+
+/// Writes through a temporary file in the same directory and renames it over
+/// `path`, so a killed daemon never leaves a truncated cover behind
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = fs::write(&tmp_path, bytes).and_then(|()| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+fn cache_album_art(url: &str, cache_max_mb: u64) -> Option<String> {
     if url.is_empty() {
         return None;
     }
@@ -122,45 +293,116 @@ fn cache_album_art(url: &str) -> Option<String> {
     let cache_path = cache_dir.join(format!("{}.jpg", hash));
 
     if cache_path.exists() {
+        touch_cover(&cache_path);
         return Some(cache_path.to_string_lossy().to_string());
     }
 
-    if url.starts_with("http") {
-        if let Ok(response) = reqwest::blocking::get(url) {
-            if let Ok(bytes) = response.bytes() {
-                if fs::write(&cache_path, &bytes).is_ok() {
-                    return Some(cache_path.to_string_lossy().to_string());
-                }
-            }
+    if url.starts_with("http")
+        && let Ok(response) = reqwest::blocking::get(url)
+        && response.status().is_success()
+        && let Ok(bytes) = response.bytes()
+        && is_complete_image(&bytes)
+        && write_atomic(&cache_path, &bytes).is_ok()
+    {
+        if cache_max_mb > 0 {
+            evict_covers(&cache_dir, cache_max_mb * 1024 * 1024, &cache_path);
         }
+        return Some(cache_path.to_string_lossy().to_string());
     }
     None
 }
 
-fn get_target_bus_name(finder: &PlayerFinder) -> Option<String> {
+/// Solid-color SVG cover derived from the artist name, cached next to real art
+fn placeholder_art(artist: &str) -> Option<String> {
+    let cache_dir = dirs::cache_dir()?.join("eww").join("covers");
+    fs::create_dir_all(&cache_dir).ok()?;
+
+    let digest = md5::compute(artist.as_bytes());
+    let cache_path = cache_dir.join(format!("placeholder-{:x}.svg", digest));
+    if !cache_path.exists() {
+        // Keep channels in the mid range so light and dark text both stay readable
+        let [r, g, b] = [digest[0], digest[1], digest[2]].map(|c| 64 + c / 2);
+        let svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"512\" height=\"512\">\
+             <rect width=\"512\" height=\"512\" fill=\"#{:02x}{:02x}{:02x}\"/></svg>",
+            r, g, b
+        );
+        fs::write(&cache_path, svg).ok()?;
+    }
+    Some(cache_path.to_string_lossy().to_string())
+}
+
+/// All players minus the `--ignore`d ones, in the order used for cycling
+fn find_players(finder: &PlayerFinder, ignore: &[String]) -> Result<Vec<Player>, FindingError> {
+    let mut players: Vec<Player> = finder
+        .find_all()?
+        .into_iter()
+        .filter(|p| {
+            let identity = p.identity().to_lowercase();
+            !ignore
+                .iter()
+                .any(|pattern| identity.contains(&pattern.to_lowercase()))
+        })
+        .collect();
+    players.sort_by_key(|p| (p.identity().to_string(), p.bus_name().to_string()));
+    Ok(players)
+}
+
+fn get_target_bus_name(finder: &PlayerFinder, ignore: &[String]) -> Option<String> {
+    let players = find_players(finder, ignore).ok()?;
+
     if let Ok(bus_name) = fs::read_to_string(SWITCH_FILE) {
         let clean = bus_name.trim().to_string();
-        if let Ok(players) = finder.find_all() {
-            if players.iter().any(|p| p.bus_name() == clean) {
-                return Some(clean);
-            }
+        if players.iter().any(|p| p.bus_name() == clean) {
+            return Some(clean);
         }
     }
 
-    if let Ok(players) = finder.find_all() {
-        let mut sorted_players: Vec<_> = players.iter().collect();
-        sorted_players.sort_by_key(|p| (p.identity().to_string(), p.bus_name().to_string()));
+    // Players that refuse every method (CanControl false) are only picked when
+    // switched to explicitly or when nothing else is left
+    let controllable: Vec<&Player> = players
+        .iter()
+        .filter(|p| p.can_control().unwrap_or(false))
+        .collect();
+    controllable
+        .iter()
+        .find(|p| p.get_playback_status().ok() == Some(PlaybackStatus::Playing))
+        .or(controllable.first())
+        .copied()
+        .or(players.first())
+        .map(|p| p.bus_name().to_string())
+}
 
-        for p in &sorted_players {
-            if p.get_playback_status().ok() == Some(PlaybackStatus::Playing) {
-                return Some(p.bus_name().to_string());
-            }
-        }
-        if let Some(first) = sorted_players.first() {
-            return Some(first.bus_name().to_string());
-        }
-    }
-    None
+fn get_tracklist(finder: &PlayerFinder, ignore: &[String]) -> Vec<TrackEntry> {
+    let Some(bus_name) = get_target_bus_name(finder, ignore) else {
+        return Vec::new();
+    };
+    let Ok(players) = find_players(finder, ignore) else {
+        return Vec::new();
+    };
+    let Some(player) = players.iter().find(|p| p.bus_name() == bus_name) else {
+        return Vec::new();
+    };
+
+    // Players without the TrackList interface report Ok(None)
+    let Ok(Some(track_list)) = player.checked_get_track_list() else {
+        return Vec::new();
+    };
+    let ids = track_list.ids();
+    let Ok(metadata) = player.get_tracks_metadata(ids) else {
+        return Vec::new();
+    };
+
+    metadata
+        .iter()
+        .filter_map(|m| {
+            Some(TrackEntry {
+                id: m.track_id()?.as_str().to_string(),
+                title: m.title().unwrap_or("Unknown Title").to_string(),
+                artist: m.artists().map(|a| a.join(", ")).unwrap_or_default(),
+            })
+        })
+        .collect()
 }
 
 struct PlayerDisplayInfo {
@@ -168,19 +410,41 @@ struct PlayerDisplayInfo {
     display_name: String,
 }
 
-fn collect_state(finder: &PlayerFinder) -> EwwMusicState {
-    let players = match finder.find_all() {
+/// Hash of the sorted bus names, so widgets can tell a player set change apart
+/// from playback progress
+fn players_hash(players: &[PlayerDisplayInfo]) -> String {
+    let mut bus_names: Vec<&str> = players.iter().map(|p| p.bus_name.as_str()).collect();
+    bus_names.sort_unstable();
+    let mut hasher = DefaultHasher::new();
+    bus_names.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Shortens `text` to at most `max` characters, the last being an ellipsis
+fn truncate_chars(text: &str, max: usize) -> String {
+    if max == 0 || text.chars().count() <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max - 1).collect();
+    format!("{}…", kept.trim_end())
+}
+
+fn collect_state(
+    finder: &PlayerFinder,
+    ignore: &[String],
+    max_title: usize,
+    max_artist: usize,
+    cache_max_mb: u64,
+) -> EwwMusicState {
+    let sorted_players = match find_players(finder, ignore) {
         Ok(l) => l,
         Err(_) => return EwwMusicState::default(),
     };
 
-    if players.is_empty() {
+    if sorted_players.is_empty() {
         return EwwMusicState::default();
     }
 
-    let mut sorted_players: Vec<_> = players.iter().collect();
-    sorted_players.sort_by_key(|p| (p.identity().to_string(), p.bus_name().to_string()));
-
     let mut counts: HashMap<String, usize> = HashMap::new();
     for p in &sorted_players {
         *counts.entry(p.identity().to_string()).or_insert(0) += 1;
@@ -206,7 +470,7 @@ fn collect_state(finder: &PlayerFinder) -> EwwMusicState {
         });
     }
 
-    let target_bus = get_target_bus_name(finder);
+    let target_bus = get_target_bus_name(finder, ignore);
     let active_idx = match target_bus {
         Some(ref bus) => player_list
             .iter()
@@ -241,34 +505,43 @@ fn collect_state(finder: &PlayerFinder) -> EwwMusicState {
         .and_then(|m| m.album_name())
         .unwrap_or("")
         .to_string();
-    let art_url = cache_album_art(metadata.as_ref().and_then(|m| m.art_url()).unwrap_or(""))
-        .unwrap_or_default();
+    let art_url = cache_album_art(
+        metadata.as_ref().and_then(|m| m.art_url()).unwrap_or(""),
+        cache_max_mb,
+    )
+    .unwrap_or_default();
 
     let length_secs = metadata
         .as_ref()
         .and_then(|m| m.length())
-        .map(|d| d.as_secs())
-        .unwrap_or(0);
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
     let position_secs = active_player_obj
         .get_position()
         .ok()
-        .map(|d| d.as_secs())
-        .unwrap_or(0);
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
 
-    let position_percent = if length_secs > 0 {
-        (position_secs as f64 * 100.0) / length_secs as f64
+    let position_percent = if length_secs > 0.0 {
+        (position_secs * 100.0) / length_secs
     } else {
         0.0
     };
 
+    let volume = active_player_obj.get_volume().unwrap_or(0.0);
+
     EwwMusicState {
+        has_player: true,
         active_player: active_info.display_name.clone(),
         active_bus: active_info.bus_name.clone(),
         available_players: player_list.iter().map(|p| p.display_name.clone()).collect(),
+        players_hash: players_hash(&player_list),
         next_player: player_list[next_idx].display_name.clone(),
         prev_player: player_list[prev_idx].display_name.clone(),
-        title,
-        artist,
+        title: truncate_chars(&title, max_title),
+        artist: truncate_chars(&artist, max_artist),
+        title_full: title,
+        artist_full: artist,
         album,
         art_url,
         playing: active_player_obj.get_playback_status().ok() == Some(PlaybackStatus::Playing),
@@ -279,15 +552,19 @@ fn collect_state(finder: &PlayerFinder) -> EwwMusicState {
                 .ok()
                 .unwrap_or(PlaybackStatus::Stopped)
         ),
+        position_secs,
+        length_secs,
         position_percent,
-        position_time: format_time(position_secs),
-        duration_time: format_time(length_secs),
-        volume: active_player_obj.get_volume().unwrap_or(0.0),
+        position_time: format_time(position_secs as u64),
+        duration_time: format_time(length_secs as u64),
+        volume,
+        volume_percent: (volume * 100.0).round().clamp(0.0, 100.0) as u8,
         can_play: active_player_obj.can_play().unwrap_or(false),
         can_pause: active_player_obj.can_pause().unwrap_or(false),
         can_go_next: active_player_obj.can_go_next().unwrap_or(false),
         can_go_previous: active_player_obj.can_go_previous().unwrap_or(false),
         can_seek: active_player_obj.can_seek().unwrap_or(false),
+        can_control: active_player_obj.can_control().unwrap_or(false),
         shuffle: active_player_obj.get_shuffle().unwrap_or(false),
         loop_status: match active_player_obj
             .get_loop_status()
@@ -302,33 +579,31 @@ fn collect_state(finder: &PlayerFinder) -> EwwMusicState {
     }
 }
 
-fn perform_action(command: Commands) {
+fn dbus_error(e: impl std::fmt::Display) -> String {
+    e.to_string()
+}
+
+fn perform_action(command: Commands, ignore: &[String]) -> Result<(), String> {
     // Commands that don't require looking up the player first
     match command {
         Commands::Switch { bus_name } => {
-            let _ = fs::write(SWITCH_FILE, bus_name);
-            return;
+            return fs::write(SWITCH_FILE, bus_name).map_err(|e| e.to_string());
         }
         Commands::Cycle { direction } => {
-            let finder = PlayerFinder::new().expect("DBus error");
-            let players = match finder.find_all() {
-                Ok(l) => l,
-                Err(_) => return,
-            };
-            if players.is_empty() {
-                return;
+            let finder = PlayerFinder::new().map_err(dbus_error)?;
+            // Same order as collect_state
+            let sorted_players = find_players(&finder, ignore).map_err(dbus_error)?;
+            if sorted_players.is_empty() {
+                return Err("No players found".to_string());
             }
 
-            // Sort exactly like collect_state
-            let mut sorted_players: Vec<_> = players.iter().collect();
-            sorted_players.sort_by_key(|p| (p.identity().to_string(), p.bus_name().to_string()));
-
             let bus_names: Vec<String> = sorted_players
                 .iter()
                 .map(|p| p.bus_name().to_string())
                 .collect();
 
-            let current_bus = get_target_bus_name(&finder).unwrap_or_else(|| bus_names[0].clone());
+            let current_bus =
+                get_target_bus_name(&finder, ignore).unwrap_or_else(|| bus_names[0].clone());
             let current_idx = bus_names
                 .iter()
                 .position(|b| b == &current_bus)
@@ -341,125 +616,226 @@ fn perform_action(command: Commands) {
                 (current_idx + count - 1) % count
             };
 
-            let _ = fs::write(SWITCH_FILE, &bus_names[new_idx]);
-            return;
+            return fs::write(SWITCH_FILE, &bus_names[new_idx]).map_err(|e| e.to_string());
         }
-        Commands::Listen => unreachable!(),
+        Commands::SwitchToPlaying { query } => {
+            let finder = PlayerFinder::new().map_err(dbus_error)?;
+            // Same order as collect_state so ties resolve predictably
+            let sorted_players = find_players(&finder, ignore).map_err(dbus_error)?;
+
+            let query = query.to_lowercase();
+            let matches: Vec<_> = sorted_players
+                .into_iter()
+                .filter(|p| {
+                    p.get_metadata()
+                        .ok()
+                        .and_then(|m| m.title().map(|t| t.to_lowercase().contains(&query)))
+                        .unwrap_or(false)
+                })
+                .collect();
+
+            let chosen = matches
+                .iter()
+                .find(|p| p.get_playback_status().ok() == Some(PlaybackStatus::Playing))
+                .or(matches.first())
+                .ok_or_else(|| format!("No player has a title matching '{}'", query))?;
+
+            return fs::write(SWITCH_FILE, chosen.bus_name()).map_err(|e| e.to_string());
+        }
+        Commands::Listen { .. } | Commands::Tracklist => unreachable!(),
         _ => {}
     }
 
     // Commands that act on the active player
-    let finder = PlayerFinder::new().expect("DBus error");
-    if let Some(bus_name) = get_target_bus_name(&finder) {
-        if let Ok(players) = finder.find_all() {
-            if let Some(player) = players.iter().find(|p| p.bus_name() == bus_name) {
-                match command {
-                    Commands::PlayPause => {
-                        if player.get_playback_status().ok() == Some(PlaybackStatus::Playing) {
-                            let _ = player.pause();
-                        } else {
-                            let _ = player.play();
-                        }
-                    }
-                    Commands::Next => {
-                        let _ = player.next();
-                    }
-                    Commands::Previous => {
-                        let _ = player.previous();
-                    }
-                    Commands::Seek { percent } => {
-                        if let Ok(metadata) = player.get_metadata() {
-                            if let Some(len) = metadata.length() {
-                                if let Some(track_id) = metadata.track_id() {
-                                    let total = len.as_micros() as f64;
-                                    let target = (total * (percent / 100.0)) as i64;
-                                    let _ = player.set_position(
-                                        track_id,
-                                        &Duration::from_micros(target as u64),
-                                    );
-                                }
-                            }
-                        }
-                    }
-                    Commands::Volume { action } => {
-                        log_to_file(&format!("Received input: '{}'", action));
-
-                        let current = player.get_volume().unwrap_or(1.0);
-                        let new_vol = match action.as_str() {
-                            // Relative steps (from eventbox scroll)
-                            "up" => (current + 0.05).min(1.0),
-                            "down" => (current - 0.05).max(0.0),
-
-                            // Numeric input (from scale or absolute value)
-                            val => {
-                                let num = val.parse::<f64>().unwrap_or(current);
-                                if num > 1.0 {
-                                    // Treat as 0-100 scale (e.g. "99" -> 0.99)
-                                    (num / 100.0).clamp(0.0, 1.0)
-                                } else if num < 0.0 {
-                                    // Treat negative numbers as 0
-                                    0.0
-                                } else {
-                                    // Treat as 0.0-1.0 scale
-                                    num.clamp(0.0, 1.0)
-                                }
-                            }
-                        };
-
-                        log_to_file(&format!("Setting volume to: {}", new_vol));
-                        let _ = player.set_volume(new_vol);
-                    }
-                    Commands::Shuffle => {
-                        let c = player.get_shuffle().unwrap_or(false);
-                        let _ = player.set_shuffle(!c);
-                    }
-                    Commands::Loop => {
-                        let c = player.get_loop_status().unwrap_or(LoopStatus::None);
-                        let n = match c {
-                            LoopStatus::None => LoopStatus::Playlist,
-                            LoopStatus::Playlist => LoopStatus::Track,
-                            LoopStatus::Track => LoopStatus::None,
-                        };
-                        let _ = player.set_loop_status(n);
-                    }
-                    Commands::PlaybackRate { rate } => {
-                        // Handle both percentage (25-400) and decimal (0.25-4.0) formats
-                        let actual_rate = if rate > 4.0 {
-                            // Treat as percentage (25-400 -> 0.25-4.0)
-                            (rate / 100.0).clamp(0.25, 4.0)
-                        } else {
-                            // Treat as decimal (0.25-4.0)
-                            rate.clamp(0.25, 4.0)
-                        };
-                        let _ = player.set_playback_rate(actual_rate);
+    let finder = PlayerFinder::new().map_err(dbus_error)?;
+    let bus_name = get_target_bus_name(&finder, ignore).ok_or("No active player")?;
+    let players = find_players(&finder, ignore).map_err(dbus_error)?;
+    let player = players
+        .iter()
+        .find(|p| p.bus_name() == bus_name)
+        .ok_or("Active player disappeared")?;
+
+    match command {
+        Commands::PlayPause => {
+            if player.get_playback_status().ok() == Some(PlaybackStatus::Playing) {
+                player.pause().map_err(dbus_error)
+            } else {
+                player.play().map_err(dbus_error)
+            }
+        }
+        Commands::Next => player.next().map_err(dbus_error),
+        Commands::Previous => player.previous().map_err(dbus_error),
+        Commands::Seek { percent } => {
+            let metadata = player.get_metadata().map_err(dbus_error)?;
+            let len = metadata.length().ok_or("Track has no known length")?;
+            let track_id = metadata.track_id().ok_or("Track has no track id")?;
+            let total = len.as_micros() as f64;
+            let target = (total * (percent / 100.0)) as i64;
+            player
+                .set_position(track_id, &Duration::from_micros(target as u64))
+                .map_err(dbus_error)
+        }
+        Commands::Volume { action } => {
+            log_to_file(&format!("Received input: '{}'", action));
+
+            let current = player.get_volume().unwrap_or(1.0);
+            let new_vol = match action.as_str() {
+                // Relative steps (from eventbox scroll)
+                "up" => (current + 0.05).min(1.0),
+                "down" => (current - 0.05).max(0.0),
+
+                // Numeric input (from scale or absolute value)
+                val => {
+                    let num = val.parse::<f64>().unwrap_or(current);
+                    if num > 1.0 {
+                        // Treat as 0-100 scale (e.g. "99" -> 0.99)
+                        (num / 100.0).clamp(0.0, 1.0)
+                    } else if num < 0.0 {
+                        // Treat negative numbers as 0
+                        0.0
+                    } else {
+                        // Treat as 0.0-1.0 scale
+                        num.clamp(0.0, 1.0)
                     }
-                    _ => {}
                 }
-            }
+            };
+
+            log_to_file(&format!("Setting volume to: {}", new_vol));
+            player.set_volume(new_vol).map_err(dbus_error)
+        }
+        Commands::Shuffle => {
+            let c = player.get_shuffle().unwrap_or(false);
+            player.set_shuffle(!c).map_err(dbus_error)
         }
+        Commands::Loop => {
+            let c = player.get_loop_status().unwrap_or(LoopStatus::None);
+            let n = match c {
+                LoopStatus::None => LoopStatus::Playlist,
+                LoopStatus::Playlist => LoopStatus::Track,
+                LoopStatus::Track => LoopStatus::None,
+            };
+            player.set_loop_status(n).map_err(dbus_error)
+        }
+        Commands::PlaybackRate { rate, rate_format } => player
+            .set_playback_rate(effective_rate(rate, rate_format))
+            .map_err(dbus_error),
+        Commands::PlaybackRateReset => player.set_playback_rate(1.0).map_err(dbus_error),
+        Commands::PlaybackRateStep { delta } => {
+            // Players without a readable rate don't support changing it
+            let current = player
+                .get_playback_rate()
+                .map_err(|_| "Player does not support changing the playback rate")?;
+            let rate = (current + delta).clamp(MIN_PLAYBACK_RATE, MAX_PLAYBACK_RATE);
+            // Round off float drift so repeated 0.1 steps land on 1.1, 1.2, ...
+            player
+                .set_playback_rate((rate * 100.0).round() / 100.0)
+                .map_err(dbus_error)
+        }
+        _ => Ok(()),
     }
 }
 
+/// Interprets a requested rate (percent 25-400 or decimal 0.25-4.0) and clamps it
+fn effective_rate(rate: f64, format: RateFormat) -> f64 {
+    let percent = match format {
+        RateFormat::Auto => rate > MAX_PLAYBACK_RATE,
+        RateFormat::Percent => true,
+        RateFormat::Decimal => false,
+    };
+    let rate = if percent { rate / 100.0 } else { rate };
+    rate.clamp(MIN_PLAYBACK_RATE, MAX_PLAYBACK_RATE)
+}
+
+/// Predicts the position `elapsed` after `polled` was read from the player
+fn interpolate_position(polled: &EwwMusicState, elapsed: Duration) -> EwwMusicState {
+    let mut state = polled.clone();
+    if !state.playing || state.length_secs <= 0.0 {
+        return state;
+    }
+    state.position_secs = (polled.position_secs + elapsed.as_secs_f64() * polled.playback_rate)
+        .min(state.length_secs);
+    state.position_percent = state.position_secs * 100.0 / state.length_secs;
+    state.position_time = format_time(state.position_secs as u64);
+    state
+}
+
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    let ignore = cli.ignore;
     match cli.command {
-        Commands::Listen => {
-            let mut interval = tokio::time::interval(Duration::from_millis(500));
+        Commands::Listen {
+            default_art,
+            interpolate,
+            max_title,
+            max_artist,
+            cache_max_mb,
+        } => {
+            let poll_interval = Duration::from_millis(POLL_INTERVAL_MS);
+            let tick = if interpolate {
+                Duration::from_millis(INTERPOLATE_TICK_MS)
+            } else {
+                poll_interval
+            };
+            let mut interval = tokio::time::interval(tick);
+            let mut last_poll: Option<(EwwMusicState, Instant)> = None;
             loop {
                 interval.tick().await;
-                let state = tokio::task::spawn_blocking(move || {
+
+                // Between real polls only emit predictions while playing;
+                // the next poll replaces them and corrects any drift
+                if let Some((polled, at)) = &last_poll
+                    && at.elapsed() < poll_interval
+                {
+                    if polled.playing
+                        && let Ok(json) =
+                            serde_json::to_string(&interpolate_position(polled, at.elapsed()))
+                    {
+                        println!("{}", json);
+                        let _ = std::io::stdout().flush();
+                    }
+                    continue;
+                }
+
+                let ignore = ignore.clone();
+                let mut state = tokio::task::spawn_blocking(move || {
                     let f = PlayerFinder::new().unwrap();
-                    collect_state(&f)
+                    collect_state(&f, &ignore, max_title, max_artist, cache_max_mb)
                 })
                 .await
                 .unwrap_or_default();
+                if state.art_url.is_empty() {
+                    state.art_url = match &default_art {
+                        Some(path) => path.to_string_lossy().to_string(),
+                        None => placeholder_art(&state.artist_full).unwrap_or_default(),
+                    };
+                }
                 if let Ok(json) = serde_json::to_string(&state) {
                     println!("{}", json);
                     let _ = std::io::stdout().flush();
                 }
+                if interpolate {
+                    last_poll = Some((state, Instant::now()));
+                }
+            }
+        }
+        Commands::Tracklist => {
+            let finder = PlayerFinder::new().expect("DBus error");
+            if let Ok(json) = serde_json::to_string(&get_tracklist(&finder, &ignore)) {
+                println!("{}", json);
+            }
+        }
+        cmd => {
+            let result = match perform_action(cmd, &ignore) {
+                Ok(()) => ActionResult {
+                    ok: true,
+                    error: String::new(),
+                },
+                Err(error) => ActionResult { ok: false, error },
+            };
+            if let Ok(json) = serde_json::to_string(&result) {
+                println!("{}", json);
             }
         }
-        cmd => perform_action(cmd),
     }
 }
@@ -1,5 +1,47 @@
+use clap::{Parser, Subcommand};
 use serde::Serialize;
-use std::process::Command;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Remembers when a device's connection was first seen: "<uuid> <unix secs>".
+// One file per device, suffixed with "-<device>".
+const UPTIME_FILE: &str = "/tmp/eww-network-uptime";
+
+// Answers every request with an empty 204 unless something intercepts it
+const PORTAL_PROBE_URL: &str = "http://connectivitycheck.gstatic.com/generate_204";
+
+#[derive(Parser)]
+#[command(name = "eww-network", about = "Network status for eww")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    /// Report this interface instead of the best connection
+    #[arg(long, conflicts_with = "all", global = true)]
+    interface: Option<String>,
+
+    /// Report every managed WiFi and ethernet device as a list
+    #[arg(long, global = true)]
+    all: bool,
+
+    /// Probe for a captive portal when WiFi is connected (costs a request)
+    #[arg(long, global = true)]
+    check_portal: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Print on every NetworkManager change and at least every poll interval,
+    /// skipping lines identical to the previous one
+    Listen {
+        /// Seconds between refreshes when NetworkManager reports nothing
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+        poll_interval: u64,
+    },
+}
 
 #[derive(Serialize)]
 struct Network {
@@ -7,90 +49,344 @@ struct Network {
     net_type: String,
     icon: String,
     name: String,
-    percent: u32,
-}
-
-fn get_network_status() -> Network {
-    let output = Command::new("nmcli")
-        .args(&["-t", "-f", "TYPE,STATE", "device"])
-        .output();
-
-    if let Ok(out) = output {
-        let text = String::from_utf8_lossy(&out.stdout);
-
-        // Check for WiFi connection
-        if text.contains("wifi:connected") {
-            let ssid_output = Command::new("nmcli")
-                .args(&["-t", "-f", "active,ssid", "dev", "wifi"])
-                .output();
-
-            if let Ok(ssid_out) = ssid_output {
-                let ssid_text = String::from_utf8_lossy(&ssid_out.stdout);
-                for line in ssid_text.lines() {
-                    if line.starts_with("yes:") {
-                        let ssid = line.strip_prefix("yes:").unwrap_or("WiFi").to_string();
-
-                        // Get signal strength
-                        let signal_output = Command::new("nmcli")
-                            .args(&["-t", "-f", "active,signal", "dev", "wifi"])
-                            .output();
-
-                        let signal = if let Ok(sig_out) = signal_output {
-                            let sig_text = String::from_utf8_lossy(&sig_out.stdout);
-                            sig_text
-                                .lines()
-                                .find(|l| l.starts_with("yes:"))
-                                .and_then(|l| l.strip_prefix("yes:"))
-                                .and_then(|s| s.parse::<u32>().ok())
-                                .unwrap_or(50)
-                        } else {
-                            50
-                        };
-
-                        let icon = if signal >= 80 {
-                            "󰤨"
-                        } else if signal >= 60 {
-                            "󰤥"
-                        } else if signal >= 40 {
-                            "󰤢"
-                        } else if signal >= 20 {
-                            "󰤟"
-                        } else {
-                            "󰤯"
-                        };
-
-                        return Network {
-                            net_type: "wifi".to_string(),
-                            icon: icon.to_string(),
-                            name: ssid,
-                            percent: signal,
-                        };
-                    }
-                }
-            }
+    interface: String,    // Device name, empty when nothing is connected
+    percent: Option<u32>, // None when the WiFi signal could not be read
+    error: String,        // Empty unless net_type is "unknown"
+    metered: bool,
+    connected_since_secs: u64, // Seconds the current connection has been up
+    captive_portal: bool,      // Only probed with --check-portal
+}
+
+impl Network {
+    fn unknown(error: String) -> Self {
+        Network {
+            net_type: "unknown".to_string(),
+            icon: "󰤫".to_string(),
+            name: "Unknown".to_string(),
+            interface: String::new(),
+            percent: None,
+            error,
+            metered: false,
+            connected_since_secs: 0,
+            captive_portal: false,
         }
+    }
 
-        // Check for Ethernet connection
-        if text.contains("ethernet:connected") {
-            return Network {
-                net_type: "ethernet".to_string(),
-                icon: "󰈀".to_string(),
-                name: "Ethernet".to_string(),
-                percent: 100,
-            };
+    fn disconnected(interface: &str) -> Self {
+        Network {
+            net_type: "disconnected".to_string(),
+            icon: "󰤭".to_string(),
+            name: "Disconnected".to_string(),
+            interface: interface.to_string(),
+            percent: Some(0),
+            error: String::new(),
+            metered: false,
+            connected_since_secs: 0,
+            captive_portal: false,
         }
     }
+}
+
+// A row of `nmcli -t -f DEVICE,TYPE,STATE device`
+struct Device {
+    name: String,
+    kind: String,
+    state: String,
+}
+
+impl Device {
+    fn is_connected(&self) -> bool {
+        self.state.starts_with("connected")
+    }
+}
 
-    // Disconnected
+// Runs nmcli in terse mode, telling a missing binary apart from a failing one
+fn run_nmcli(args: &[&str]) -> Result<String, String> {
+    let out = Command::new("nmcli").args(args).output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            "nmcli not found".to_string()
+        } else {
+            format!("Failed to run nmcli: {}", e)
+        }
+    })?;
+
+    if !out.status.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+        return Err(format!("nmcli error: {}", stderr));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).to_string())
+}
+
+fn list_devices() -> Result<Vec<Device>, String> {
+    let text = run_nmcli(&["-t", "-f", "DEVICE,TYPE,STATE", "device"])?;
+    Ok(text
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, ':');
+            Some(Device {
+                name: fields.next()?.to_string(),
+                kind: fields.next()?.to_string(),
+                state: fields.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+// Value of a single terse `nmcli <object> show` field
+fn show_field(args: &[&str], field: &str) -> Option<String> {
+    let mut full_args = vec!["-t", "-f", field];
+    full_args.extend_from_slice(args);
+    let text = run_nmcli(&full_args).ok()?;
+    let value = text.lines().next()?.split_once(':')?.1.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+// NetworkManager reports "yes"/"no", optionally suffixed with "(guessed)"
+fn is_metered(device: &str) -> bool {
+    show_field(&["device", "show", device], "GENERAL.METERED")
+        .map(|value| value.starts_with("yes") || value == "guess-yes")
+        .unwrap_or(false)
+}
+
+fn uptime_file(device: &str) -> String {
+    format!("{}-{}", UPTIME_FILE, device)
+}
+
+// NetworkManager only refreshes connection.timestamp periodically while a
+// connection is up, so it seeds the start time once per connection UUID and
+// the cached value is used from then on.
+fn connected_since_secs(device: &str) -> u64 {
+    let Some(uuid) = show_field(&["device", "show", device], "GENERAL.CON-UUID") else {
+        return 0;
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let cached = std::fs::read_to_string(uptime_file(device))
+        .ok()
+        .and_then(|text| {
+            let (cached_uuid, since) = text.trim().split_once(' ')?;
+            (cached_uuid == uuid).then(|| since.parse::<u64>().ok())?
+        });
+
+    let since = cached.unwrap_or_else(|| {
+        let activated = show_field(
+            &["connection", "show", "uuid", &uuid],
+            "connection.timestamp",
+        )
+        .and_then(|ts| ts.parse::<u64>().ok())
+        .filter(|&ts| ts > 0)
+        .unwrap_or(now)
+        .min(now);
+        let _ = std::fs::write(uptime_file(device), format!("{} {}", uuid, activated));
+        activated
+    });
+
+    now.saturating_sub(since)
+}
+
+// A portal answers the probe with a redirect or its login page instead of the
+// expected 204. Requests that fail outright are not counted as a portal.
+fn behind_captive_portal() -> bool {
+    let Ok(client) = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(3))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+    else {
+        return false;
+    };
+
+    client
+        .get(PORTAL_PROBE_URL)
+        .send()
+        .map(|response| response.status() != reqwest::StatusCode::NO_CONTENT)
+        .unwrap_or(false)
+}
+
+fn wifi_status(device: &str) -> Option<Network> {
+    let text = run_nmcli(&[
+        "-t",
+        "-f",
+        "active,signal,ssid",
+        "dev",
+        "wifi",
+        "list",
+        "ifname",
+        device,
+    ])
+    .ok()?;
+    let line = text.lines().find_map(|l| l.strip_prefix("yes:"))?;
+    let (signal, ssid) = line.split_once(':').unwrap_or((line, "WiFi"));
+    let signal = signal.parse::<u32>().ok();
+
+    let icon = match signal {
+        None => "󰖩",
+        Some(s) if s >= 80 => "󰤨",
+        Some(s) if s >= 60 => "󰤥",
+        Some(s) if s >= 40 => "󰤢",
+        Some(s) if s >= 20 => "󰤟",
+        Some(_) => "󰤯",
+    };
+
+    Some(Network {
+        net_type: "wifi".to_string(),
+        icon: icon.to_string(),
+        name: ssid.to_string(),
+        interface: device.to_string(),
+        percent: signal,
+        error: String::new(),
+        metered: is_metered(device),
+        connected_since_secs: connected_since_secs(device),
+        captive_portal: false,
+    })
+}
+
+fn ethernet_status(device: &str) -> Network {
     Network {
-        net_type: "disconnected".to_string(),
-        icon: "󰤭".to_string(),
-        name: "Disconnected".to_string(),
-        percent: 0,
+        net_type: "ethernet".to_string(),
+        icon: "󰈀".to_string(),
+        name: "Ethernet".to_string(),
+        interface: device.to_string(),
+        percent: Some(100),
+        error: String::new(),
+        metered: is_metered(device),
+        connected_since_secs: connected_since_secs(device),
+        captive_portal: false,
+    }
+}
+
+fn device_status(device: &Device) -> Network {
+    if !device.is_connected() {
+        return Network::disconnected(&device.name);
+    }
+    match device.kind.as_str() {
+        "wifi" => wifi_status(&device.name).unwrap_or_else(|| Network::disconnected(&device.name)),
+        _ => ethernet_status(&device.name),
+    }
+}
+
+// Best connection: WiFi first, then ethernet
+fn get_network_status(devices: &[Device]) -> Network {
+    let connected = |kind: &'static str| {
+        devices
+            .iter()
+            .filter(move |d| d.kind == kind && d.is_connected())
+    };
+
+    connected("wifi")
+        .find_map(|d| wifi_status(&d.name))
+        .or_else(|| {
+            connected("ethernet")
+                .next()
+                .map(|d| ethernet_status(&d.name))
+        })
+        .unwrap_or_else(|| Network::disconnected(""))
+}
+
+// JSON for the selected mode: one network, or a list with --all
+fn render(cli: &Cli) -> String {
+    let devices = match list_devices() {
+        Ok(devices) => devices,
+        Err(e) => {
+            let status = Network::unknown(e);
+            return if cli.all {
+                serde_json::to_string(&[status]).unwrap()
+            } else {
+                serde_json::to_string(&status).unwrap()
+            };
+        }
+    };
+
+    // Disconnected devices start a fresh uptime on their next connection
+    for device in devices.iter().filter(|d| !d.is_connected()) {
+        let _ = std::fs::remove_file(uptime_file(&device.name));
+    }
+
+    if cli.all {
+        let mut statuses: Vec<Network> = devices
+            .iter()
+            .filter(|d| matches!(d.kind.as_str(), "wifi" | "ethernet"))
+            .filter(|d| d.state != "unmanaged")
+            .map(device_status)
+            .collect();
+        if cli.check_portal && statuses.iter().any(|s| s.net_type == "wifi") {
+            let portal = behind_captive_portal();
+            for status in statuses.iter_mut().filter(|s| s.net_type == "wifi") {
+                status.captive_portal = portal;
+            }
+        }
+        return serde_json::to_string(&statuses).unwrap();
+    }
+
+    let mut status = match &cli.interface {
+        Some(name) => match devices.iter().find(|d| &d.name == name) {
+            Some(device) => device_status(device),
+            None => Network::unknown(format!("No such interface: {}", name)),
+        },
+        None => get_network_status(&devices),
+    };
+    if cli.check_portal && status.net_type == "wifi" {
+        status.captive_portal = behind_captive_portal();
+    }
+    serde_json::to_string(&status).unwrap()
+}
+
+// Lines from `nmcli monitor`, one per NetworkManager state change
+fn spawn_monitor() -> Option<Receiver<()>> {
+    let mut child = Command::new("nmcli")
+        .arg("monitor")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let stdout = child.stdout.take()?;
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for _ in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if tx.send(()).is_err() {
+                break;
+            }
+        }
+        let _ = child.wait();
+    });
+    Some(rx)
+}
+
+fn listen(cli: &Cli, poll_interval: u64) {
+    let interval = Duration::from_secs(poll_interval);
+    let mut monitor = spawn_monitor();
+    let mut last = String::new();
+
+    loop {
+        let line = render(cli);
+        if line != last {
+            println!("{}", line);
+            last = line;
+        }
+
+        match monitor.as_ref().map(|rx| rx.recv_timeout(interval)) {
+            Some(Ok(())) => {
+                // One change usually comes as a burst of lines
+                while monitor.as_ref().is_some_and(|rx| rx.try_recv().is_ok()) {}
+            }
+            Some(Err(RecvTimeoutError::Timeout)) => {}
+            Some(Err(RecvTimeoutError::Disconnected)) => {
+                eprintln!("nmcli monitor exited, polling only");
+                monitor = None;
+            }
+            None => thread::sleep(interval),
+        }
     }
 }
 
 fn main() {
-    let status = get_network_status();
-    println!("{}", serde_json::to_string(&status).unwrap());
+    let cli = Cli::parse();
+
+    match cli.command {
+        Some(Commands::Listen { poll_interval }) => listen(&cli, poll_interval),
+        None => println!("{}", render(&cli)),
+    }
 }
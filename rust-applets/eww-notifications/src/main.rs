@@ -5,13 +5,15 @@ use zbus::{Connection, Result};
 struct Notifications {
     count: u32,
     dnd: bool,
+    error: String, // Empty unless the notification daemon couldn't be queried
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let notifications = get_notifications().await.unwrap_or(Notifications {
+    let notifications = get_notifications().await.unwrap_or_else(|e| Notifications {
         count: 0,
         dnd: false,
+        error: e.to_string(),
     });
     println!("{}", serde_json::to_string(&notifications).unwrap());
     Ok(())
@@ -36,16 +38,14 @@ async fn get_notifications() -> Result<Notifications> {
 
     // Get swaync-specific properties
     // Count: cc.cccounts.swaync
-    let count: u32 = proxy
-        .get_property("cc.cccounts.swaync")
-        .await
-        .unwrap_or(0);
+    let count: u32 = proxy.get_property("cc.cccounts.swaync").await?;
 
     // DND status
-    let dnd: bool = proxy
-        .get_property("cc.cccounts.swaync.dnd")
-        .await
-        .unwrap_or(false);
+    let dnd: bool = proxy.get_property("cc.cccounts.swaync.dnd").await?;
 
-    Ok(Notifications { count, dnd })
+    Ok(Notifications {
+        count,
+        dnd,
+        error: String::new(),
+    })
 }
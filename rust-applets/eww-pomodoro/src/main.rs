@@ -1,12 +1,13 @@
 use anyhow::Context;
 use clap::{Parser, Subcommand};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 use std::io::{BufRead, BufReader, Write};
 use std::os::unix::net::{UnixListener, UnixStream};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use zbus::zvariant::OwnedObjectPath;
 
 // --- CONFIGURATION CONSTANTS AND STRUCT ---
 
@@ -15,7 +16,16 @@ const DEFAULT_WORK_DURATION: u32 = 1500; // 25 minutes
 const DEFAULT_SHORT_BREAK: u32 = 300; // 5 minutes
 const DEFAULT_LONG_BREAK: u32 = 900; // 15 minutes
 const DEFAULT_LONG_BREAK_INTERVAL: u32 = 4; // Sessions before long break
+const DEFAULT_TIME_FORMAT: &str = "%m:%S"; // M:SS
 const DEFAULT_SOCKET_PATH: &str = "/tmp/eww-pomodoro.sock";
+const PERSIST_INTERVAL_SECS: u64 = 5; // How often the tick thread writes the state file
+const STALE_STATE_SECS: u64 = 600; // Running timers older than this restore as paused
+const DEFAULT_TICK_MS: u64 = 1000;
+const DBUS_NAME: &str = "org.eww.Pomodoro";
+const DBUS_PATH: &str = "/org/eww/Pomodoro";
+const SUBSCRIBER_BUFFER: usize = 8; // State lines queued per Listen client
+const SUBSCRIBER_MAX_DROPS: u32 = 30; // Consecutive dropped lines before a client is pruned
+const SUBSCRIBER_WRITE_TIMEOUT_SECS: u64 = 5;
 
 /// Holds all the timing configuration for the Pomodoro timer.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +35,17 @@ struct PomodoroConfig {
     short_break: u32,
     long_break: u32,
     long_break_interval: u32,
+    // Pattern for time_display: %H/%h hours, %M/%m minutes, %S/%s seconds
+    // (upper case zero-pads to two digits, %% is a literal percent sign)
+    #[serde(default = "default_time_format")]
+    time_format: String,
+    // Also emit time_display_long (H:MM:SS once the phase is over an hour)
+    #[serde(default)]
+    long_time_display: bool,
+}
+
+fn default_time_format() -> String {
+    DEFAULT_TIME_FORMAT.to_string()
 }
 
 impl Default for PomodoroConfig {
@@ -34,6 +55,8 @@ impl Default for PomodoroConfig {
             short_break: DEFAULT_SHORT_BREAK,
             long_break: DEFAULT_LONG_BREAK,
             long_break_interval: DEFAULT_LONG_BREAK_INTERVAL,
+            time_format: default_time_format(),
+            long_time_display: false,
         }
     }
 }
@@ -79,6 +102,75 @@ struct Cli {
         help = "Path to custom configuration file (default: ~/.config/eww-pomodoro/config.json)"
     )]
     config: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Start the daemon fresh instead of restoring the saved timer"
+    )]
+    no_restore: bool,
+
+    #[arg(
+        long,
+        help = "Pause the timer while the screen is locked and resume on unlock"
+    )]
+    pause_on_lock: bool,
+
+    #[arg(
+        long,
+        default_value_t = DEFAULT_TICK_MS,
+        value_parser = clap::value_parser!(u64).range(1..=1000),
+        help = "Milliseconds between timer ticks (1-1000); below 1000 percent is reported to 0.01"
+    )]
+    tick_ms: u64,
+
+    #[arg(
+        long,
+        help = "Also expose the timer as org.eww.Pomodoro on the session bus"
+    )]
+    dbus: bool,
+
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Ignore a Skip arriving within this many milliseconds of the previous one (0 disables)"
+    )]
+    skip_debounce_ms: u64,
+
+    #[arg(
+        long,
+        value_name = "MINUTES",
+        help = "Start work sessions on the next clock boundary that is a multiple of MINUTES"
+    )]
+    align_to: Option<u32>,
+
+    #[arg(long, help = "Don't send desktop notifications when a phase ends")]
+    no_notify: bool,
+}
+
+/// Daemon behaviour switches resolved from the command line.
+#[derive(Debug, Clone, Copy)]
+struct DaemonOptions {
+    restore: bool,
+    pause_on_lock: bool,
+    tick_ms: u64,
+    dbus: bool,
+    skip_debounce_ms: u64,
+    align_to_mins: u32,
+    notify: bool,
+}
+
+impl DaemonOptions {
+    fn from_cli(cli: &Cli) -> Self {
+        Self {
+            restore: !cli.no_restore,
+            pause_on_lock: cli.pause_on_lock,
+            tick_ms: cli.tick_ms,
+            dbus: cli.dbus,
+            skip_debounce_ms: cli.skip_debounce_ms,
+            align_to_mins: cli.align_to.unwrap_or(0),
+            notify: !cli.no_notify,
+        }
+    }
 }
 
 // ... (CliCommand and PomodoroState remain the same)
@@ -90,6 +182,8 @@ enum CliCommand {
     Toggle,
     Stop,
     Skip,
+    /// Zero the session counter without touching the current phase
+    ResetSessions,
     GetState,
     Kill,
 }
@@ -99,14 +193,63 @@ struct PomodoroState {
     status: String,
     time_left: u32,
     time_display: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_display_long: Option<String>,
+    elapsed: u32, // Seconds spent in the current phase (duration - time_left)
+    elapsed_display: String,
     sessions: u32,
     is_break: bool,
-    percent: u32,
+    #[serde(serialize_with = "serialize_percent")]
+    percent: f64, // Progress through the phase, sub-second accurate with --tick-ms
     icon: String,
 }
 
+/// Whole percents are written as integers, so the default one-second tick
+/// prints `"percent":50` rather than `50.0`
+fn serialize_percent<S: Serializer>(percent: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    if percent.fract() == 0.0 {
+        serializer.serialize_u64(*percent as u64)
+    } else {
+        serializer.serialize_f64(*percent)
+    }
+}
+
+/// Core timer fields written to disk so a restarted daemon can resume the session.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct PersistedTimer {
+    status: String,
+    time_left: u32,
+    sessions: u32,
+    is_break: bool,
+    duration: u32,
+    saved_at: u64, // Unix seconds, stamped when written
+}
+
 // --- TIMER IMPLEMENTATION ---
 
+/// Where phase-change notifications go; swapped out so tests don't spawn processes.
+trait Notifier: Send {
+    fn notify(&self, summary: &str, body: &str);
+}
+
+/// Desktop notifications through `notify-send`.
+struct NotifySend;
+
+impl Notifier for NotifySend {
+    fn notify(&self, summary: &str, body: &str) {
+        let _ = std::process::Command::new("notify-send")
+            .args([summary, body, "-u", "normal"])
+            .spawn();
+    }
+}
+
+/// Drops notifications, for --no-notify.
+struct NoNotify;
+
+impl Notifier for NoNotify {
+    fn notify(&self, _summary: &str, _body: &str) {}
+}
+
 struct PomodoroTimer {
     config: PomodoroConfig, // Store the final, resolved config
     status: String,
@@ -115,6 +258,14 @@ struct PomodoroTimer {
     is_break: bool,
     running: bool,
     duration: u32, // The total duration of the current phase (used for percent calculation)
+    paused_by_lock: bool, // Set when a screen lock paused a running timer
+    pending_ms: u32, // Milliseconds ticked toward the next whole second
+    fine_percent: bool, // Count pending_ms in percent (--tick-ms below 1000)
+    skip_debounce: Duration, // Skips closer together than this are ignored
+    last_skip: Option<Instant>,
+    align_to_mins: u32, // Clock boundary work sessions start on (0 = start immediately)
+    wait_left: u32,     // Seconds until an aligned work session starts ("waiting" status)
+    notifier: Box<dyn Notifier>,
 }
 
 impl PomodoroTimer {
@@ -126,23 +277,58 @@ impl PomodoroTimer {
             is_break: false,
             running: false,
             duration: config.work_duration,
+            paused_by_lock: false,
+            pending_ms: 0,
+            fine_percent: false,
+            skip_debounce: Duration::ZERO,
+            last_skip: None,
+            align_to_mins: 0,
+            wait_left: 0,
+            notifier: Box::new(NotifySend),
             config,
         }
     }
 
     fn get_state(&self) -> PomodoroState {
-        let time_display = format_time(self.time_left);
-        let percent = if self.duration > 0 {
-            ((self.duration.saturating_sub(self.time_left)) * 100) / self.duration
+        // While waiting for an aligned start, count down to the boundary instead
+        let waiting = self.wait_left > 0;
+        let time_left = if waiting {
+            self.wait_left
         } else {
+            self.time_left
+        };
+        let time_display = format_time(time_left, &self.config.time_format);
+        let time_display_long = self.config.long_time_display.then(|| {
+            if self.duration > 3600 {
+                format_time(time_left, "%h:%M:%S")
+            } else {
+                time_display.clone()
+            }
+        });
+        let elapsed = if waiting {
             0
+        } else {
+            self.duration.saturating_sub(self.time_left)
+        };
+        let elapsed_display = format_time(elapsed, &self.config.time_format);
+        let percent = if self.duration == 0 || waiting {
+            0.0
+        } else if self.fine_percent {
+            let elapsed_ms = elapsed as f64 * 1000.0 + self.pending_ms as f64;
+            let percent = elapsed_ms * 100.0 / (self.duration as f64 * 1000.0);
+            (percent * 100.0).round() / 100.0
+        } else {
+            (elapsed * 100 / self.duration) as f64
         };
         let icon = if self.is_break { "休憩" } else { "作業" };
 
         PomodoroState {
             status: self.status.clone(),
-            time_left: self.time_left,
+            time_left,
             time_display,
+            time_display_long,
+            elapsed,
+            elapsed_display,
             sessions: self.sessions,
             is_break: self.is_break,
             percent,
@@ -151,7 +337,15 @@ impl PomodoroTimer {
     }
 
     fn toggle(&mut self) {
-        if self.running {
+        self.paused_by_lock = false;
+        if self.wait_left > 0 {
+            // Cancel the pending aligned start and un-count its session
+            self.wait_left = 0;
+            self.pending_ms = 0;
+            self.running = false;
+            self.status = "idle".to_string();
+            self.sessions = self.sessions.saturating_sub(1);
+        } else if self.running {
             self.status = "paused".to_string();
             self.running = false;
         } else if self.status == "paused" {
@@ -159,14 +353,17 @@ impl PomodoroTimer {
             self.running = true;
         } else {
             // Starting a new session from idle/toggled back on
+            let was_idle = self.status == "idle";
             self.status = "running".to_string();
             self.running = true;
 
             // Determine the duration if the timer was at 0 before starting,
             // otherwise continue the current time_left/duration
-            if self.time_left == 0 || self.status == "idle" {
+            if self.time_left == 0 || was_idle {
                 if self.is_break {
-                    self.duration = if self.sessions % self.config.long_break_interval == 0
+                    self.duration = if self
+                        .sessions
+                        .is_multiple_of(self.config.long_break_interval)
                         && self.sessions > 0
                     {
                         self.config.long_break
@@ -178,11 +375,23 @@ impl PomodoroTimer {
                     self.sessions = self.sessions.saturating_add(1);
                 }
                 self.time_left = self.duration;
+                self.pending_ms = 0;
+            }
+
+            if !self.is_break && self.align_to_mins > 0 {
+                self.wait_left = secs_until_aligned(local_unix_now(), self.align_to_mins);
+                if self.wait_left > 0 {
+                    self.status = "waiting".to_string();
+                    self.pending_ms = 0;
+                }
             }
         }
     }
 
     fn stop(&mut self) {
+        self.paused_by_lock = false;
+        self.pending_ms = 0;
+        self.wait_left = 0;
         self.status = "idle".to_string();
         self.running = false;
         self.time_left = self.config.work_duration;
@@ -192,6 +401,20 @@ impl PomodoroTimer {
     }
 
     fn skip(&mut self) {
+        // Swallow accidental double-taps on the skip keybind
+        if self
+            .last_skip
+            .is_some_and(|at| at.elapsed() < self.skip_debounce)
+        {
+            return;
+        }
+        self.last_skip = Some(Instant::now());
+
+        // A work phase that was started has already been counted
+        let started = self.status != "idle";
+        self.paused_by_lock = false;
+        self.pending_ms = 0;
+        self.wait_left = 0;
         // Stop running state immediately
         self.running = false;
         self.status = "idle".to_string();
@@ -204,8 +427,13 @@ impl PomodoroTimer {
         } else {
             // Skip work, start next break
             self.is_break = true;
-            self.sessions = self.sessions.saturating_add(1); // Count the session that was skipped
-            self.duration = if self.sessions % self.config.long_break_interval == 0 {
+            if !started {
+                self.sessions = self.sessions.saturating_add(1); // Count the session that was skipped
+            }
+            self.duration = if self
+                .sessions
+                .is_multiple_of(self.config.long_break_interval)
+            {
                 self.config.long_break
             } else {
                 self.config.short_break
@@ -214,39 +442,132 @@ impl PomodoroTimer {
         }
     }
 
-    fn tick(&mut self) -> bool {
-        if !self.running || self.time_left == 0 {
+    fn reset_sessions(&mut self) {
+        // A work phase already in progress was counted when it started; keep it
+        // as session 1 so finishing it doesn't hit the `0 % interval` long break
+        self.sessions = if !self.is_break && self.status != "idle" {
+            1
+        } else {
+            0
+        };
+    }
+
+    fn on_screen_lock(&mut self) {
+        // A lock doesn't move the clock boundary, so waiting carries on
+        if self.running && self.wait_left == 0 {
+            self.toggle();
+            self.paused_by_lock = true;
+        }
+    }
+
+    fn on_screen_unlock(&mut self) {
+        // Only resume timers the lock paused, never ones the user paused
+        if self.paused_by_lock && self.status == "paused" {
+            self.toggle();
+        }
+        self.paused_by_lock = false;
+    }
+
+    fn snapshot(&self) -> PersistedTimer {
+        PersistedTimer {
+            status: self.status.clone(),
+            time_left: self.time_left,
+            sessions: self.sessions,
+            is_break: self.is_break,
+            duration: self.duration,
+            saved_at: 0,
+        }
+    }
+
+    fn restore(&mut self, saved: PersistedTimer, now: u64) {
+        self.status = saved.status;
+        self.time_left = saved.time_left;
+        self.sessions = saved.sessions;
+        self.is_break = saved.is_break;
+        self.duration = saved.duration;
+        self.pending_ms = 0;
+        self.running = self.status == "running";
+
+        if self.status == "waiting" {
+            // The boundary has most likely passed; go back to idle and un-count
+            // the session so the next start aligns afresh
+            self.status = "idle".to_string();
+            self.sessions = self.sessions.saturating_sub(1);
+        }
+
+        if self.running {
+            let elapsed = now.saturating_sub(saved.saved_at);
+            if elapsed > STALE_STATE_SECS {
+                // Too old to catch up on wall-clock time; resume where we left off
+                self.status = "paused".to_string();
+                self.running = false;
+            } else {
+                // Keep at least one second so the next tick performs the phase change
+                let elapsed = u32::try_from(elapsed).unwrap_or(u32::MAX);
+                self.time_left = self.time_left.saturating_sub(elapsed).max(1);
+            }
+        }
+    }
+
+    /// Advance the timer by `elapsed_ms`; whole seconds come off `time_left`.
+    /// Returns true when a phase ended.
+    fn tick(&mut self, elapsed_ms: u32) -> bool {
+        if !self.running {
             return false;
         }
 
-        self.time_left = self.time_left.saturating_sub(1);
+        self.pending_ms += elapsed_ms;
+        let mut phase_ended = false;
+        while self.running && self.pending_ms >= 1000 {
+            self.pending_ms -= 1000;
+            phase_ended |= self.tick_second();
+        }
+        phase_ended
+    }
+
+    /// Take one second off the aligned-start wait or the running phase
+    fn tick_second(&mut self) -> bool {
+        if self.wait_left > 0 {
+            self.wait_left -= 1;
+            if self.wait_left == 0 {
+                // Boundary reached: the prepared work phase starts now
+                self.pending_ms = 0;
+                self.status = "running".to_string();
+            }
+            return false;
+        }
 
         if self.time_left == 0 {
+            return false;
+        }
+        self.time_left -= 1;
+
+        if self.time_left == 0 {
+            self.pending_ms = 0;
             self.running = false;
             self.status = "idle".to_string();
 
             if self.is_break {
                 // Break finished
-                let _ = std::process::Command::new("notify-send")
-                    .args(["Break Over!", "Time to focus!", "-u", "normal"])
-                    .spawn();
+                self.notifier.notify("Break Over!", "Time to focus!");
 
                 self.is_break = false;
                 self.time_left = self.config.work_duration;
                 self.duration = self.config.work_duration;
             } else {
                 // Work finished
-                if self.sessions % self.config.long_break_interval == 0 {
-                    let _ = std::process::Command::new("notify-send")
-                        .args(["Pomodoro Complete!", "Take a long break!", "-u", "normal"])
-                        .spawn();
+                if self
+                    .sessions
+                    .is_multiple_of(self.config.long_break_interval)
+                {
+                    self.notifier
+                        .notify("Pomodoro Complete!", "Take a long break!");
                     self.is_break = true;
                     self.time_left = self.config.long_break;
                     self.duration = self.config.long_break;
                 } else {
-                    let _ = std::process::Command::new("notify-send")
-                        .args(["Pomodoro Complete!", "Take a short break!", "-u", "normal"])
-                        .spawn();
+                    self.notifier
+                        .notify("Pomodoro Complete!", "Take a short break!");
                     self.is_break = true;
                     self.time_left = self.config.short_break;
                     self.duration = self.config.short_break;
@@ -301,6 +622,8 @@ fn load_config(cli: &Cli) -> anyhow::Result<PomodoroConfig> {
         config.short_break = file_config.short_break;
         config.long_break = file_config.long_break;
         config.long_break_interval = file_config.long_break_interval;
+        config.time_format = file_config.time_format;
+        config.long_time_display = file_config.long_time_display;
     } else {
         // If config file doesn't exist, create it with default values for user editing
         let default_json = serde_json::to_string_pretty(&config)?;
@@ -328,11 +651,227 @@ fn load_config(cli: &Cli) -> anyhow::Result<PomodoroConfig> {
     Ok(config)
 }
 
-// Helper function to format seconds into MM:SS string
-fn format_time(total_seconds: u32) -> String {
-    let minutes = total_seconds / 60;
+// --- STATE PERSISTENCE ---
+
+fn get_state_path() -> Option<PathBuf> {
+    let state_dir = dirs::state_dir()
+        .or_else(dirs::cache_dir)?
+        .join("eww-pomodoro");
+    std::fs::create_dir_all(&state_dir).ok()?;
+    Some(state_dir.join("state.json"))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Local wall-clock time in seconds, so boundaries land on local :00/:25/...
+fn local_unix_now() -> u64 {
+    let now = unix_now();
+    let t = now as libc::time_t;
+    // SAFETY: localtime_r only writes to the tm we pass in
+    let offset = unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&t, &mut tm).is_null() {
+            0
+        } else {
+            tm.tm_gmtoff
+        }
+    };
+    now.saturating_add_signed(offset)
+}
+
+/// Seconds from `now` until the next multiple of `align_mins` minutes (0 when on one)
+fn secs_until_aligned(now: u64, align_mins: u32) -> u32 {
+    let period = u64::from(align_mins) * 60;
+    match now % period {
+        0 => 0,
+        rem => (period - rem) as u32,
+    }
+}
+
+fn save_timer_state(path: &Path, snapshot: &PersistedTimer) {
+    let stamped = PersistedTimer {
+        saved_at: unix_now(),
+        ..snapshot.clone()
+    };
+    if let Ok(json) = serde_json::to_string(&stamped) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+fn load_timer_state(path: &Path) -> Option<PersistedTimer> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+// --- SCREEN LOCK HOOK ---
+
+fn spawn_lock_watchers(timer: Arc<Mutex<PomodoroTimer>>) {
+    let login1_timer = timer.clone();
+    thread::spawn(move || {
+        if let Err(e) = watch_login1_session(&login1_timer) {
+            eprintln!("login1 lock signals unavailable: {}", e);
+        }
+    });
+    thread::spawn(move || {
+        if let Err(e) = watch_screensaver(&timer) {
+            eprintln!("ScreenSaver signals unavailable: {}", e);
+        }
+    });
+}
+
+/// Follows Lock/Unlock on our logind session (system bus)
+fn watch_login1_session(timer: &Mutex<PomodoroTimer>) -> zbus::Result<()> {
+    let conn = zbus::blocking::Connection::system()?;
+    let manager = zbus::blocking::Proxy::new(
+        &conn,
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1",
+        "org.freedesktop.login1.Manager",
+    )?;
+
+    let session: OwnedObjectPath = match std::env::var("XDG_SESSION_ID") {
+        Ok(id) => manager.call("GetSession", &(id,))?,
+        Err(_) => manager.call("GetSessionByPID", &(std::process::id(),))?,
+    };
+
+    let proxy = zbus::blocking::Proxy::new(
+        &conn,
+        "org.freedesktop.login1",
+        session,
+        "org.freedesktop.login1.Session",
+    )?;
+
+    for msg in proxy.receive_all_signals()? {
+        let header = msg.header();
+        let Ok(mut t) = timer.lock() else { continue };
+        match header.member().map(|m| m.as_str()) {
+            Some("Lock") => t.on_screen_lock(),
+            Some("Unlock") => t.on_screen_unlock(),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Follows the freedesktop ScreenSaver ActiveChanged signal (session bus)
+fn watch_screensaver(timer: &Mutex<PomodoroTimer>) -> zbus::Result<()> {
+    let conn = zbus::blocking::Connection::session()?;
+    let proxy = zbus::blocking::Proxy::new(
+        &conn,
+        "org.freedesktop.ScreenSaver",
+        "/org/freedesktop/ScreenSaver",
+        "org.freedesktop.ScreenSaver",
+    )?;
+
+    for msg in proxy.receive_signal("ActiveChanged")? {
+        let Ok(active) = msg.body().deserialize::<bool>() else {
+            continue;
+        };
+        if let Ok(mut t) = timer.lock() {
+            if active {
+                t.on_screen_lock();
+            } else {
+                t.on_screen_unlock();
+            }
+        }
+    }
+    Ok(())
+}
+
+// --- D-BUS INTERFACE ---
+
+/// org.eww.Pomodoro: a mirror of the socket commands for non-eww tools.
+/// State changes are announced by the tick thread as StateChanged(json).
+struct PomodoroDbus {
+    timer: Arc<Mutex<PomodoroTimer>>,
+}
+
+#[zbus::interface(name = "org.eww.Pomodoro")]
+impl PomodoroDbus {
+    /// Current state as the same JSON the socket emits
+    fn get_state(&self) -> zbus::fdo::Result<String> {
+        let state = self.lock()?.get_state();
+        serde_json::to_string(&state).map_err(|e| zbus::fdo::Error::Failed(e.to_string()))
+    }
+
+    fn toggle(&self) -> zbus::fdo::Result<()> {
+        self.lock()?.toggle();
+        Ok(())
+    }
+
+    fn stop(&self) -> zbus::fdo::Result<()> {
+        self.lock()?.stop();
+        Ok(())
+    }
+
+    fn skip(&self) -> zbus::fdo::Result<()> {
+        self.lock()?.skip();
+        Ok(())
+    }
+
+    /// Declared for introspection; emitted by the tick thread with the state JSON
+    #[zbus(signal)]
+    async fn state_changed(
+        emitter: &zbus::object_server::SignalEmitter<'_>,
+        state: &str,
+    ) -> zbus::Result<()>;
+}
+
+impl PomodoroDbus {
+    fn lock(&self) -> zbus::fdo::Result<std::sync::MutexGuard<'_, PomodoroTimer>> {
+        self.timer
+            .lock()
+            .map_err(|_| zbus::fdo::Error::Failed("timer state poisoned".to_string()))
+    }
+}
+
+fn serve_dbus(timer: Arc<Mutex<PomodoroTimer>>) -> zbus::Result<zbus::blocking::Connection> {
+    zbus::blocking::connection::Builder::session()?
+        .name(DBUS_NAME)?
+        .serve_at(DBUS_PATH, PomodoroDbus { timer })?
+        .build()
+}
+
+// Helper function to format seconds using a time_format pattern (see PomodoroConfig)
+fn format_time(total_seconds: u32, pattern: &str) -> String {
+    // Minutes only wrap at the hour when the pattern shows hours
+    let has_hours = pattern.contains("%H") || pattern.contains("%h");
+    let hours = total_seconds / 3600;
+    let minutes = if has_hours {
+        (total_seconds % 3600) / 60
+    } else {
+        total_seconds / 60
+    };
     let seconds = total_seconds % 60;
-    format!("{}:{:02}", minutes, seconds)
+
+    let mut out = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('H') => out.push_str(&format!("{:02}", hours)),
+            Some('h') => out.push_str(&hours.to_string()),
+            Some('M') => out.push_str(&format!("{:02}", minutes)),
+            Some('m') => out.push_str(&minutes.to_string()),
+            Some('S') => out.push_str(&format!("{:02}", seconds)),
+            Some('s') => out.push_str(&seconds.to_string()),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
 }
 
 // --- DAEMON AND MAIN FUNCTIONS ---
@@ -352,8 +891,36 @@ fn retry_connect(socket_path: &str, retries: u32, sleep_ms: u64) -> anyhow::Resu
     ))
 }
 
+/// A Listen client's queue. Lines are dropped while it is full, and a client
+/// that stays full is pruned so a stuck widget can't grow the daemon's memory.
+struct Subscriber {
+    tx: std::sync::mpsc::SyncSender<String>,
+    dropped: u32,
+}
+
+impl Subscriber {
+    /// Queue a line; false once the client is gone or has fallen too far behind
+    fn offer(&mut self, line: &str) -> bool {
+        match self.tx.try_send(line.to_string()) {
+            Ok(()) => {
+                self.dropped = 0;
+                true
+            }
+            Err(std::sync::mpsc::TrySendError::Full(_)) => {
+                self.dropped += 1;
+                self.dropped < SUBSCRIBER_MAX_DROPS
+            }
+            Err(std::sync::mpsc::TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
 // Update run_daemon to accept the resolved config
-fn run_daemon(socket_path: &str, config: PomodoroConfig) -> anyhow::Result<()> {
+fn run_daemon(
+    socket_path: &str,
+    config: PomodoroConfig,
+    options: DaemonOptions,
+) -> anyhow::Result<()> {
     // If a daemon is already running and using the socket, exit early.
     if std::path::Path::new(socket_path).exists() {
         if UnixStream::connect(socket_path).is_ok() {
@@ -364,9 +931,36 @@ fn run_daemon(socket_path: &str, config: PomodoroConfig) -> anyhow::Result<()> {
     }
 
     let listener = UnixListener::bind(socket_path)?;
-    let timer = Arc::new(Mutex::new(PomodoroTimer::new(config))); // Pass config here
-    let subscribers: Arc<Mutex<Vec<std::sync::mpsc::Sender<String>>>> =
-        Arc::new(Mutex::new(Vec::new()));
+
+    let state_path = get_state_path();
+    let mut pomodoro = PomodoroTimer::new(config); // Pass config here
+    pomodoro.skip_debounce = Duration::from_millis(options.skip_debounce_ms);
+    pomodoro.fine_percent = options.tick_ms < 1000;
+    pomodoro.align_to_mins = options.align_to_mins;
+    if !options.notify {
+        pomodoro.notifier = Box::new(NoNotify);
+    }
+    if options.restore {
+        if let Some(saved) = state_path.as_deref().and_then(load_timer_state) {
+            pomodoro.restore(saved, unix_now());
+        }
+    }
+    let timer = Arc::new(Mutex::new(pomodoro));
+    if options.pause_on_lock {
+        spawn_lock_watchers(timer.clone());
+    }
+    let dbus = if options.dbus {
+        match serve_dbus(timer.clone()) {
+            Ok(conn) => Some(conn),
+            Err(e) => {
+                eprintln!("Failed to register {} on the session bus: {}", DBUS_NAME, e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let subscribers: Arc<Mutex<Vec<Subscriber>>> = Arc::new(Mutex::new(Vec::new()));
 
     // ARC the socket path string so it can be moved into the Kill command thread
     let socket_path_arc = Arc::new(socket_path.to_string());
@@ -374,23 +968,61 @@ fn run_daemon(socket_path: &str, config: PomodoroConfig) -> anyhow::Result<()> {
     // Tick thread
     let tick_timer = timer.clone();
     let tick_subs = subscribers.clone();
+    let tick_state_path = state_path.clone();
+    let tick_ms = options.tick_ms;
     thread::spawn(move || {
         let mut last_state_json = String::new();
+        let mut last_persisted: Option<PersistedTimer> = None;
+        let persist_every = PERSIST_INTERVAL_SECS * 1000 / tick_ms;
+        let mut ticks: u64 = 0;
+        let mut last_tick = Instant::now();
         loop {
-            thread::sleep(Duration::from_secs(1));
-
-            if let Ok(mut t) = tick_timer.lock() {
-                t.tick();
-                let state = t.get_state();
-
-                if let Ok(json) = serde_json::to_string(&state) {
-                    if json != last_state_json {
-                        last_state_json = json.clone();
-                        let mut list = tick_subs.lock().unwrap();
-                        // Retain only the subscribers that are still alive (send successful)
-                        list.retain(|tx| tx.send(json.clone()).is_ok());
+            thread::sleep(Duration::from_millis(tick_ms));
+            ticks += 1;
+
+            // Sleeps overshoot, so count the time that actually passed
+            let now = Instant::now();
+            let elapsed_ms = now.duration_since(last_tick).as_millis();
+            last_tick = now;
+
+            let state = match tick_timer.lock() {
+                Ok(mut t) => {
+                    t.tick(u32::try_from(elapsed_ms).unwrap_or(u32::MAX));
+
+                    if let Some(path) = tick_state_path.as_deref() {
+                        let snapshot = t.snapshot();
+                        if ticks.is_multiple_of(persist_every)
+                            && last_persisted.as_ref() != Some(&snapshot)
+                        {
+                            save_timer_state(path, &snapshot);
+                            last_persisted = Some(snapshot);
+                        }
                     }
+                    t.get_state()
                 }
+                Err(_) => continue,
+            };
+
+            let Ok(json) = serde_json::to_string(&state) else {
+                continue;
+            };
+            if json == last_state_json {
+                continue;
+            }
+            last_state_json = json.clone();
+
+            // Retain only the subscribers that are alive and keeping up
+            tick_subs.lock().unwrap().retain_mut(|sub| sub.offer(&json));
+
+            // A blocking bus call, so it runs with no lock held
+            if let Some(conn) = &dbus {
+                let _ = conn.emit_signal(
+                    None::<&str>,
+                    DBUS_PATH,
+                    DBUS_NAME,
+                    "StateChanged",
+                    &(json.as_str(),),
+                );
             }
         }
     });
@@ -402,6 +1034,7 @@ fn run_daemon(socket_path: &str, config: PomodoroConfig) -> anyhow::Result<()> {
                 let sub_list = subscribers.clone();
                 // Clone the Arc for the thread that might handle Kill
                 let socket_path_for_kill = socket_path_arc.clone();
+                let state_path = state_path.clone();
 
                 thread::spawn(move || {
                     let mut de = serde_json::Deserializer::from_reader(&stream);
@@ -409,22 +1042,31 @@ fn run_daemon(socket_path: &str, config: PomodoroConfig) -> anyhow::Result<()> {
                         match cmd {
                             CliCommand::Daemon => {}
                             CliCommand::Kill => {
+                                // Persist the latest timer so the next daemon resumes it
+                                if let (Some(path), Ok(t)) = (state_path.as_deref(), timer.lock()) {
+                                    save_timer_state(path, &t.snapshot());
+                                }
                                 // Use the owned path from the Arc
                                 let _ = std::fs::remove_file(socket_path_for_kill.as_str());
                                 std::process::exit(0)
                             }
                             CliCommand::Listen => {
-                                let (tx, rx) = std::sync::mpsc::channel();
+                                let (tx, rx) = std::sync::mpsc::sync_channel(SUBSCRIBER_BUFFER);
+                                // A client that stops reading fails its writes instead of
+                                // blocking this thread forever
+                                let _ = stream.set_write_timeout(Some(Duration::from_secs(
+                                    SUBSCRIBER_WRITE_TIMEOUT_SECS,
+                                )));
                                 if let Ok(t) = timer.lock() {
                                     let state = t.get_state();
                                     if let Ok(j) = serde_json::to_string(&state) {
-                                        let _ = write!(stream, "{}\n", j);
+                                        let _ = writeln!(stream, "{}", j);
                                     }
                                 }
-                                sub_list.lock().unwrap().push(tx);
+                                sub_list.lock().unwrap().push(Subscriber { tx, dropped: 0 });
                                 while let Ok(msg) = rx.recv() {
                                     // Exit loop if client stream is closed/broken
-                                    if write!(stream, "{}\n", msg).is_err() {
+                                    if writeln!(stream, "{}", msg).is_err() {
                                         break;
                                     }
                                 }
@@ -446,6 +1088,10 @@ fn run_daemon(socket_path: &str, config: PomodoroConfig) -> anyhow::Result<()> {
                                 timer.lock().unwrap().skip();
                                 let _ = serde_json::to_writer(&stream, &DaemonResponse::Success);
                             }
+                            CliCommand::ResetSessions => {
+                                timer.lock().unwrap().reset_sessions();
+                                let _ = serde_json::to_writer(&stream, &DaemonResponse::Success);
+                            }
                         }
                     }
                 });
@@ -493,7 +1139,11 @@ fn send_client_command(socket_path: &str, cmd: CliCommand, cli_args: &Cli) -> an
 
                     // Execute the daemon logic directly in this process.
                     // This is synchronous and blocks until the listener is bound, ensuring the parent waits.
-                    if let Err(e) = run_daemon(socket_path, config_for_daemon) {
+                    if let Err(e) = run_daemon(
+                        socket_path,
+                        config_for_daemon,
+                        DaemonOptions::from_cli(cli_args),
+                    ) {
                         eprintln!("Daemon error: {}", e);
                         // Exit with an error code if the daemon fails to bind/run
                         std::process::exit(1);
@@ -549,10 +1199,196 @@ fn main() -> anyhow::Result<()> {
 
     match args.command {
         // The Daemon case needs the config passed in directly
-        CliCommand::Daemon => run_daemon(&args.socket, config),
+        CliCommand::Daemon => run_daemon(&args.socket, config, DaemonOptions::from_cli(&args)),
         // Use 'ref cmd' to BORROW args.command instead of moving it.
         // Then we clone the command (cmd.clone()) to pass an owned value
         // to send_client_command, while keeping the rest of 'args' intact for '&args'.
         ref cmd => send_client_command(&args.socket, cmd.clone(), &args),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct RecordingNotifier(Arc<Mutex<Vec<String>>>);
+
+    impl Notifier for RecordingNotifier {
+        fn notify(&self, summary: &str, body: &str) {
+            self.0.lock().unwrap().push(format!("{summary} {body}"));
+        }
+    }
+
+    // 10s work, 3s short break, 6s long break, long break every 2nd session
+    fn timer() -> (PomodoroTimer, Arc<Mutex<Vec<String>>>) {
+        let config = PomodoroConfig {
+            work_duration: 10,
+            short_break: 3,
+            long_break: 6,
+            long_break_interval: 2,
+            ..PomodoroConfig::default()
+        };
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut timer = PomodoroTimer::new(config);
+        timer.notifier = Box::new(RecordingNotifier(sent.clone()));
+        (timer, sent)
+    }
+
+    fn tick_secs(timer: &mut PomodoroTimer, secs: u32) -> bool {
+        let mut changed = false;
+        for _ in 0..secs {
+            changed |= timer.tick(1000);
+        }
+        changed
+    }
+
+    fn assert_state(
+        timer: &PomodoroTimer,
+        status: &str,
+        is_break: bool,
+        sessions: u32,
+        percent: f64,
+    ) {
+        let state = timer.get_state();
+        assert_eq!(state.status, status);
+        assert_eq!(state.is_break, is_break);
+        assert_eq!(state.sessions, sessions);
+        assert_eq!(state.percent, percent);
+    }
+
+    #[test]
+    fn work_runs_into_short_then_long_break() {
+        let (mut timer, sent) = timer();
+        assert_state(&timer, "idle", false, 0, 0.0);
+
+        timer.toggle();
+        assert_state(&timer, "running", false, 1, 0.0);
+        assert!(!tick_secs(&mut timer, 5));
+        assert_state(&timer, "running", false, 1, 50.0);
+
+        assert!(tick_secs(&mut timer, 5));
+        assert_state(&timer, "idle", true, 1, 0.0);
+        assert_eq!(timer.get_state().time_left, 3);
+
+        timer.toggle();
+        assert_state(&timer, "running", true, 1, 0.0);
+        assert!(tick_secs(&mut timer, 3));
+        assert_state(&timer, "idle", false, 1, 0.0);
+
+        timer.toggle();
+        assert!(tick_secs(&mut timer, 10));
+        assert_state(&timer, "idle", true, 2, 0.0);
+        assert_eq!(timer.get_state().time_left, 6);
+
+        assert_eq!(
+            *sent.lock().unwrap(),
+            [
+                "Pomodoro Complete! Take a short break!",
+                "Break Over! Time to focus!",
+                "Pomodoro Complete! Take a long break!",
+            ]
+        );
+    }
+
+    #[test]
+    fn pause_then_toggle_resumes_the_same_session() {
+        let (mut timer, _) = timer();
+        timer.toggle();
+        tick_secs(&mut timer, 4);
+
+        timer.toggle();
+        assert_state(&timer, "paused", false, 1, 40.0);
+        assert!(!tick_secs(&mut timer, 3));
+        assert_state(&timer, "paused", false, 1, 40.0);
+
+        timer.toggle();
+        assert_state(&timer, "running", false, 1, 40.0);
+        assert_eq!(timer.get_state().time_left, 6);
+    }
+
+    #[test]
+    fn skip_moves_between_work_and_break() {
+        let (mut timer, sent) = timer();
+
+        // Skipping a work session that never started still counts it
+        timer.skip();
+        assert_state(&timer, "idle", true, 1, 0.0);
+        assert_eq!(timer.get_state().time_left, 3);
+
+        timer.skip();
+        assert_state(&timer, "idle", false, 1, 0.0);
+        assert_eq!(timer.get_state().time_left, 10);
+
+        // A started one was counted on start and isn't counted twice
+        timer.toggle();
+        tick_secs(&mut timer, 2);
+        timer.skip();
+        assert_state(&timer, "idle", true, 2, 0.0);
+        assert_eq!(timer.get_state().time_left, 6);
+
+        assert!(sent.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn sub_second_ticks_accumulate() {
+        let (mut timer, _) = timer();
+        timer.fine_percent = true;
+        timer.toggle();
+
+        assert!(!timer.tick(500));
+        assert_state(&timer, "running", false, 1, 5.0);
+        assert_eq!(timer.get_state().time_left, 10);
+
+        timer.tick(500);
+        assert_eq!(timer.get_state().time_left, 9);
+        assert_state(&timer, "running", false, 1, 10.0);
+    }
+
+    #[test]
+    fn percent_stays_whole_at_the_default_tick() {
+        let (mut timer, _) = timer();
+        timer.toggle();
+
+        // An overshooting tick keeps its spare milliseconds; a late one
+        // catches up on every second it covered
+        timer.tick(1003);
+        assert!(!timer.tick(2400));
+        assert_eq!(timer.get_state().time_left, 7);
+        assert_state(&timer, "running", false, 1, 30.0);
+        let json = serde_json::to_string(&timer.get_state()).unwrap();
+        assert!(json.contains(r#""percent":30,"#), "{json}");
+
+        timer.fine_percent = true;
+        let json = serde_json::to_string(&timer.get_state()).unwrap();
+        assert!(json.contains(r#""percent":34.03,"#), "{json}");
+    }
+
+    #[test]
+    fn stop_resets_everything() {
+        let (mut timer, _) = timer();
+        timer.toggle();
+        tick_secs(&mut timer, 10);
+        timer.toggle();
+        tick_secs(&mut timer, 1);
+
+        timer.stop();
+        assert_state(&timer, "idle", false, 0, 0.0);
+        assert_eq!(timer.get_state().time_left, 10);
+        assert!(!tick_secs(&mut timer, 20));
+    }
+
+    #[test]
+    fn screen_lock_only_resumes_what_it_paused() {
+        let (mut timer, _) = timer();
+        timer.toggle();
+        timer.on_screen_lock();
+        assert_state(&timer, "paused", false, 1, 0.0);
+        timer.on_screen_unlock();
+        assert_state(&timer, "running", false, 1, 0.0);
+
+        timer.toggle();
+        timer.on_screen_lock();
+        timer.on_screen_unlock();
+        assert_state(&timer, "paused", false, 1, 0.0);
+    }
+}
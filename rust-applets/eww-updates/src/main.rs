@@ -1,5 +1,51 @@
+use clap::{Parser, Subcommand};
 use serde::Serialize;
+use std::path::Path;
 use std::process::Command;
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+
+const PACMAN_LOCAL_DB: &str = "/var/lib/pacman/local";
+
+// Packages whose upgrade only takes effect after a reboot
+const REBOOT_TRIGGERS: &[&str] = &[
+    "systemd",
+    "glibc",
+    "linux-firmware",
+    "amd-ucode",
+    "intel-ucode",
+    "nvidia",
+    "nvidia-dkms",
+    "nvidia-open",
+];
+
+#[derive(Parser)]
+#[command(name = "eww-updates", about = "Pending package updates for eww")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    /// Include the names of the updatable packages
+    #[arg(long, global = true)]
+    verbose: bool,
+
+    /// Longest package list --verbose reports
+    #[arg(long, global = true, default_value_t = 20)]
+    max_list: usize,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Recount periodically, printing a line each time
+    Listen {
+        /// Seconds between recounts
+        #[arg(long, default_value_t = 1800, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+        /// Notify once each time the total rises above N
+        #[arg(long, value_name = "N")]
+        notify_threshold: Option<u32>,
+    },
+}
 
 #[derive(Serialize)]
 struct Updates {
@@ -7,52 +53,187 @@ struct Updates {
     aur: u32,
     total: u32,
     icon: String,
+    reboot_required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    packages: Option<Vec<String>>, // Only with --verbose
+}
+
+// One line per pending update: "name oldver -> newver"
+fn update_lines(stdout: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect()
 }
 
-fn count_official_updates() -> u32 {
+fn official_updates() -> Vec<String> {
     // Try checkupdates first (from pacman-contrib)
     if let Ok(output) = Command::new("checkupdates").output() {
-        return output.stdout.iter().filter(|&&c| c == b'\n').count() as u32;
+        return update_lines(&output.stdout);
     }
 
     // Fallback to pacman
-    if let Ok(output) = Command::new("pacman").args(&["-Qu"]).output() {
-        return output.stdout.iter().filter(|&&c| c == b'\n').count() as u32;
+    if let Ok(output) = Command::new("pacman").args(["-Qu"]).output() {
+        return update_lines(&output.stdout);
     }
 
-    0
+    Vec::new()
 }
 
-fn count_aur_updates() -> u32 {
+fn aur_updates() -> Vec<String> {
     // Try paru first
-    if let Ok(output) = Command::new("paru").args(&["-Qua"]).output() {
-        return output.stdout.iter().filter(|&&c| c == b'\n').count() as u32;
+    if let Ok(output) = Command::new("paru").args(["-Qua"]).output() {
+        return update_lines(&output.stdout);
     }
 
     // Fallback to yay
-    if let Ok(output) = Command::new("yay").args(&["-Qua"]).output() {
-        return output.stdout.iter().filter(|&&c| c == b'\n').count() as u32;
+    if let Ok(output) = Command::new("yay").args(["-Qua"]).output() {
+        return update_lines(&output.stdout);
     }
 
-    0
+    Vec::new()
 }
 
-fn get_updates() -> Updates {
-    let official = count_official_updates();
-    let aur = count_aur_updates();
+/// `max_list` is the package list cap, or None to leave the list out
+fn get_updates(max_list: Option<usize>) -> Updates {
+    let official_lines = official_updates();
+    let aur_lines = aur_updates();
+    let official = official_lines.len() as u32;
+    let aur = aur_lines.len() as u32;
     let total = official + aur;
 
     let icon = "".to_string(); //
 
+    let packages = max_list.map(|max| {
+        official_lines
+            .iter()
+            .chain(&aur_lines)
+            .filter_map(|line| line.split_whitespace().next())
+            .map(String::from)
+            .take(max)
+            .collect()
+    });
+
     Updates {
         official,
         aur,
         total,
         icon,
+        reboot_required: reboot_required(),
+        packages,
+    }
+}
+
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Upgrading the kernel removes the running release's modules; for the stock
+// kernel the pacman version ("6.9.3.arch1-1") must also match uname ("6.9.3-arch1-1")
+fn kernel_outdated() -> bool {
+    let Some(running) = command_stdout("uname", &["-r"]) else {
+        return false;
+    };
+
+    let modules = Path::new("/usr/lib/modules");
+    if modules.is_dir() && !modules.join(&running).exists() {
+        return true;
+    }
+
+    if !running.contains("-arch") {
+        return false;
+    }
+    command_stdout("pacman", &["-Q", "linux"])
+        .and_then(|line| line.split_whitespace().nth(1).map(String::from))
+        .map(|version| version.replacen(".arch", "-arch", 1) != running)
+        .unwrap_or(false)
+}
+
+fn boot_time() -> Option<u64> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    stat.lines()
+        .find_map(|line| line.strip_prefix("btime "))?
+        .trim()
+        .parse()
+        .ok()
+}
+
+// pacman's local db has one "<name>-<pkgver>-<pkgrel>" directory per package,
+// written when it was installed or upgraded
+fn trigger_upgraded_since_boot() -> bool {
+    let Some(boot) = boot_time() else {
+        return false;
+    };
+    let Ok(entries) = std::fs::read_dir(PACMAN_LOCAL_DB) else {
+        return false;
+    };
+
+    entries.flatten().any(|entry| {
+        let dir_name = entry.file_name().to_string_lossy().to_string();
+        let name = dir_name.rsplitn(3, '-').nth(2).unwrap_or_default();
+        if !REBOOT_TRIGGERS.contains(&name) {
+            return false;
+        }
+        std::fs::metadata(entry.path().join("desc"))
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .is_some_and(|t| t.as_secs() > boot)
+    })
+}
+
+// Best effort: false whenever it can't be determined
+fn reboot_required() -> bool {
+    kernel_outdated() || trigger_upgraded_since_boot()
+}
+
+fn notify_updates(total: u32) {
+    let _ = Command::new("notify-send")
+        .args([
+            "Updates available",
+            &format!("{} packages can be upgraded", total),
+            "-u",
+            "normal",
+        ])
+        .spawn();
+}
+
+fn listen(interval: u64, notify_threshold: Option<u32>, max_list: Option<usize>) {
+    let mut above = false;
+    loop {
+        let updates = get_updates(max_list);
+        println!("{}", serde_json::to_string(&updates).unwrap());
+
+        // Only the crossing notifies; recounts that stay above are quiet
+        if let Some(threshold) = notify_threshold {
+            let now_above = updates.total > threshold;
+            if now_above && !above {
+                notify_updates(updates.total);
+            }
+            above = now_above;
+        }
+
+        thread::sleep(Duration::from_secs(interval));
     }
 }
 
 fn main() {
-    let updates = get_updates();
-    println!("{}", serde_json::to_string(&updates).unwrap());
+    let cli = Cli::parse();
+    let max_list = cli.verbose.then_some(cli.max_list);
+
+    match cli.command {
+        Some(Commands::Listen {
+            interval,
+            notify_threshold,
+        }) => listen(interval, notify_threshold, max_list),
+        None => {
+            let updates = get_updates(max_list);
+            println!("{}", serde_json::to_string(&updates).unwrap());
+        }
+    }
 }
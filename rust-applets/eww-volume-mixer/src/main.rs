@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use libpulse_binding as pulse;
 use libpulse_binding::callbacks::ListResult;
 use libpulse_binding::context::{Context, FlagSet as ContextFlagSet};
@@ -7,10 +7,11 @@ use libpulse_binding::proplist::Proplist;
 use libpulse_binding::volume::Volume;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
 use std::io::{BufRead, BufReader, Write};
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::rc::Rc;
-use std::sync::mpsc;
+use std::sync::{mpsc, Mutex};
 use std::thread;
 use std::time::Duration;
 
@@ -28,21 +29,124 @@ struct Cli {
 
 #[derive(Subcommand, Serialize, Deserialize, Debug, Clone, PartialEq)]
 enum CliCommand {
-    Listen,
+    Listen {
+        /// How slider percents map to PulseAudio volumes; reported percents
+        /// use the same curve so sliders round-trip
+        #[arg(long, value_enum, default_value_t = VolumeCurve::Linear)]
+        volume_curve: VolumeCurve,
+        /// Ceiling for SetSinkVolume and SetSinkInputVolume
+        #[arg(long, default_value_t = 150, value_parser = clap::value_parser!(u8).range(1..=150))]
+        max_volume: u8,
+        /// Percent scale for reported volumes and incoming Set*Volume values
+        #[arg(long, value_enum, default_value_t = ReportScale::Absolute)]
+        report_scale: ReportScale,
+    },
     GetState,
-    SetSinkVolume { sink_index: u32, volume: u8 },
-    SetSinkInputVolume { index: u32, volume: u8 },
-    SetDefaultSink { sink_name: String },
-    MuteSink { sink_index: u32, mute: bool },
-    ToggleMuteSink { sink_index: u32 },
+    SetSinkVolume {
+        sink_index: u32,
+        volume: u8,
+    },
+    SetSinkInputVolume {
+        index: u32,
+        volume: u8,
+    },
+    SetDefaultSink {
+        sink_name: String,
+    },
+    MuteSink {
+        sink_index: u32,
+        mute: bool,
+    },
+    ToggleMuteSink {
+        sink_index: u32,
+    },
     ToggleMuteDefault,
     VolumeUp,
     VolumeDown,
-    MuteSinkInput { index: u32, mute: bool },
-    ToggleMuteSinkInput { index: u32 },
+    MuteSinkInput {
+        index: u32,
+        mute: bool,
+    },
+    ToggleMuteSinkInput {
+        index: u32,
+    },
+    /// Set every application stream to `percent`, remembering the old volumes
+    DuckAllInputs {
+        percent: u8,
+    },
+    /// Put ducked application streams back to their remembered volumes
+    RestoreAllInputs,
+    /// Make the listening daemon print a fresh state line right away
+    Refresh,
     Kill,
 }
 
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+enum VolumeCurve {
+    /// Volume is proportional to the percent
+    Linear,
+    /// Volume is percent^3, giving finer control at low levels
+    Cubic,
+}
+
+impl VolumeCurve {
+    fn to_volume(self, percent: f64) -> Volume {
+        let p = percent / 100.0;
+        let fraction = match self {
+            VolumeCurve::Linear => p,
+            VolumeCurve::Cubic => p.powi(3),
+        };
+        Volume((Volume::NORMAL.0 as f64 * fraction).round() as u32)
+    }
+
+    /// Inverse of `to_volume`
+    fn percent(self, volume: Volume) -> f64 {
+        let fraction = volume.0 as f64 / Volume::NORMAL.0 as f64;
+        let p = match self {
+            VolumeCurve::Linear => fraction,
+            VolumeCurve::Cubic => fraction.cbrt(),
+        };
+        p * 100.0
+    }
+}
+
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+enum ReportScale {
+    /// PulseAudio's own percent, 100 being unamplified
+    Absolute,
+    /// Percent of --max-volume, so the ceiling reads as 100
+    Normalized,
+}
+
+/// Converts between PulseAudio volumes and the percents clients see and send
+#[derive(Clone, Copy)]
+struct PercentScale {
+    curve: VolumeCurve,
+    report: ReportScale,
+    max_volume: u8,
+}
+
+impl PercentScale {
+    /// Rounded so a percent that was set reads back unchanged
+    fn percent(self, volume: Volume) -> u8 {
+        let percent = self.curve.percent(volume);
+        let reported = match self.report {
+            ReportScale::Absolute => percent,
+            ReportScale::Normalized => percent * 100.0 / self.max_volume as f64,
+        };
+        reported.round().min(255.0) as u8
+    }
+
+    /// Volume for a client percent, capped at --max-volume
+    fn volume(self, percent: u8) -> Volume {
+        let percent = match self.report {
+            ReportScale::Absolute => percent as f64,
+            ReportScale::Normalized => percent as f64 * self.max_volume as f64 / 100.0,
+        };
+        self.curve.to_volume(percent.min(self.max_volume as f64))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 struct SinkInfo {
     index: u32,
@@ -64,6 +168,7 @@ struct SinkInputInfo {
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 struct MixerState {
+    audio_available: bool, // False while PulseAudio is unreachable
     percent: u8,
     muted: bool,
     level: u8,
@@ -83,15 +188,58 @@ enum ActorMessage {
     Refresh,
 }
 
+/// Print a warning the first time this exact message comes up
+fn warn_once(message: String) {
+    static WARNED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+    if let Ok(mut warned) = WARNED.lock() {
+        if warned.insert(message.clone()) {
+            eprintln!("Warning: {}", message);
+        }
+    }
+}
+
+/// Flag at most one sink as default: the first whose name matches.
+/// Applied once after enumeration, using the name from a single server-info query.
+/// When no name matches (e.g. PipeWire renamed the node), fall back to the
+/// description the default had in the previous snapshot.
+fn mark_default(
+    sinks: &mut [SinkInfo],
+    default_name: Option<&str>,
+    last_description: Option<&str>,
+) {
+    let position = sinks
+        .iter()
+        .position(|sink| Some(sink.name.as_str()) == default_name)
+        .or_else(|| {
+            let description = last_description?;
+            let position = sinks
+                .iter()
+                .position(|sink| sink.description == description)?;
+            warn_once(format!(
+                "default sink '{}' not found, matched '{}' by description",
+                default_name.unwrap_or_default(),
+                sinks[position].name
+            ));
+            Some(position)
+        });
+
+    for (i, sink) in sinks.iter_mut().enumerate() {
+        sink.is_default = Some(i) == position;
+    }
+}
+
 // --- PULSEAUDIO ACTOR ---
 
 struct PulseAudioActor {
     mainloop: Rc<RefCell<Mainloop>>,
     context: Rc<RefCell<Context>>,
+    // Description of the last sink flagged default, for rename-tolerant matching
+    default_description: RefCell<Option<String>>,
+    scale: PercentScale,
 }
 
 impl PulseAudioActor {
-    fn new() -> Result<Self, String> {
+    fn new(scale: PercentScale) -> Result<Self, String> {
         let mut proplist = Proplist::new().unwrap();
         proplist
             .set_str(
@@ -134,11 +282,20 @@ impl PulseAudioActor {
             }
         }
 
-        Ok(Self { mainloop, context })
+        Ok(Self {
+            mainloop,
+            context,
+            default_description: RefCell::new(None),
+            scale,
+        })
     }
 
     fn get_state(&self) -> MixerState {
-        let mut state = MixerState::default();
+        let mut state = MixerState {
+            audio_available: true,
+            ..Default::default()
+        };
+        let scale = self.scale;
 
         // Lock Mainloop
         self.mainloop.borrow_mut().lock();
@@ -147,17 +304,13 @@ impl PulseAudioActor {
         let introspect = self.context.borrow().introspect();
         let (tx, rx) = mpsc::channel();
         introspect.get_server_info(move |info| {
-            let name = info
-                .default_sink_name
-                .as_ref()
-                .map(|n| n.to_string())
-                .unwrap_or_default();
+            let name = info.default_sink_name.as_ref().map(|n| n.to_string());
             let _ = tx.send(name);
         });
 
         // Unlock to wait for callback
         self.mainloop.borrow_mut().unlock();
-        let default_sink_name = rx.recv().unwrap_or_default();
+        let default_sink_name = rx.recv().ok().flatten();
         self.mainloop.borrow_mut().lock();
 
         // 2. Get Sinks
@@ -165,7 +318,7 @@ impl PulseAudioActor {
         let (tx, rx) = mpsc::channel();
         introspect.get_sink_info_list(move |res| match res {
             ListResult::Item(item) => {
-                let vol = (item.volume.avg().0 as f64 / Volume::NORMAL.0 as f64 * 100.0) as u8;
+                let vol = scale.percent(item.volume.avg());
                 let _ = tx.send(Some(SinkInfo {
                     index: item.index,
                     name: item
@@ -190,8 +343,7 @@ impl PulseAudioActor {
         });
 
         self.mainloop.borrow_mut().unlock();
-        while let Ok(Some(mut sink)) = rx.recv() {
-            sink.is_default = sink.name == default_sink_name;
+        while let Ok(Some(sink)) = rx.recv() {
             state.sinks.push(sink);
         }
         self.mainloop.borrow_mut().lock();
@@ -201,7 +353,7 @@ impl PulseAudioActor {
         let (tx, rx) = mpsc::channel();
         introspect.get_sink_input_info_list(move |res| match res {
             ListResult::Item(item) => {
-                let vol = (item.volume.avg().0 as f64 / Volume::NORMAL.0 as f64 * 100.0) as u8;
+                let vol = scale.percent(item.volume.avg());
                 let name = item
                     .proplist
                     .get_str(pulse::proplist::properties::APPLICATION_NAME)
@@ -226,7 +378,18 @@ impl PulseAudioActor {
         }
 
         // Sort and finalize
-        state.sinks.sort_by(|a, b| b.is_default.cmp(&a.is_default));
+        let last_description = self.default_description.borrow().clone();
+        mark_default(
+            &mut state.sinks,
+            default_sink_name.as_deref(),
+            last_description.as_deref(),
+        );
+        *self.default_description.borrow_mut() = state
+            .sinks
+            .iter()
+            .find(|sink| sink.is_default)
+            .map(|sink| sink.description.clone());
+        state.sinks.sort_by_key(|s| std::cmp::Reverse(s.is_default));
         if let Some(def) = state.sinks.iter().find(|s| s.is_default) {
             state.percent = def.volume;
             state.muted = def.muted;
@@ -257,8 +420,7 @@ impl PulseAudioActor {
         if let Ok(Some(mut cv)) = rx.recv() {
             self.mainloop.borrow_mut().lock();
             let mut introspect = self.context.borrow().introspect();
-            let v_val = (Volume::NORMAL.0 as f64 * (percent.min(150) as f64 / 100.0)) as u32;
-            cv.scale(Volume(v_val));
+            cv.scale(self.scale.volume(percent));
             introspect.set_sink_volume_by_index(index, &cv, None);
             self.mainloop.borrow_mut().unlock();
         }
@@ -281,8 +443,7 @@ impl PulseAudioActor {
         if let Ok(Some(mut cv)) = rx.recv() {
             self.mainloop.borrow_mut().lock();
             let mut introspect = self.context.borrow().introspect();
-            let v_val = (Volume::NORMAL.0 as f64 * (percent.min(150) as f64 / 100.0)) as u32;
-            cv.scale(Volume(v_val));
+            cv.scale(self.scale.volume(percent));
             introspect.set_sink_input_volume(index, &cv, None);
             self.mainloop.borrow_mut().unlock();
         }
@@ -355,7 +516,41 @@ impl PulseAudioActor {
 
 // --- DAEMON RUNNER ---
 
-fn run_server(socket_path: &str) -> anyhow::Result<()> {
+/// Serve the "no audio server" state, retrying the connection on each refresh tick.
+/// Returns the actor once PulseAudio is reachable again.
+fn handle_without_audio(msg: ActorMessage, scale: PercentScale) -> Option<PulseAudioActor> {
+    match msg {
+        ActorMessage::Refresh => {
+            let actor = PulseAudioActor::new(scale).ok()?;
+            eprintln!("PulseAudio is back, resuming normal operation");
+            if let Ok(json) = serde_json::to_string(&actor.get_state()) {
+                println!("{}", json);
+            }
+            Some(actor)
+        }
+        ActorMessage::Command(CliCommand::GetState, reply_tx) => {
+            let _ = reply_tx.send(DaemonResponse::State(MixerState::default()));
+            None
+        }
+        ActorMessage::Command(CliCommand::Kill, _) => std::process::exit(0),
+        ActorMessage::Command(CliCommand::Refresh, reply_tx) => {
+            let actor = handle_without_audio(ActorMessage::Refresh, scale);
+            if actor.is_none() {
+                if let Ok(json) = serde_json::to_string(&MixerState::default()) {
+                    println!("{}", json);
+                }
+            }
+            let _ = reply_tx.send(DaemonResponse::Success);
+            actor
+        }
+        ActorMessage::Command(_, reply_tx) => {
+            let _ = reply_tx.send(DaemonResponse::Error("No audio server available".into()));
+            None
+        }
+    }
+}
+
+fn run_server(socket_path: &str, scale: PercentScale) -> anyhow::Result<()> {
     if std::path::Path::new(socket_path).exists() {
         let _ = std::fs::remove_file(socket_path);
     }
@@ -365,21 +560,29 @@ fn run_server(socket_path: &str) -> anyhow::Result<()> {
 
     // 1. ACTOR THREAD
     thread::spawn(move || {
-        let actor = match PulseAudioActor::new() {
-            Ok(a) => a,
+        let mut actor = match PulseAudioActor::new(scale) {
+            Ok(a) => Some(a),
             Err(e) => {
-                eprintln!("Failed to init PulseAudio: {}", e);
-                return;
+                eprintln!("PulseAudio unavailable ({}), retrying in background", e);
+                None
             }
         };
 
         // Initial state print
-        let init = actor.get_state();
+        let init = actor.as_ref().map(|a| a.get_state()).unwrap_or_default();
         if let Ok(json) = serde_json::to_string(&init) {
             println!("{}", json);
         }
 
+        // Pre-duck volumes keyed by application name, so they survive streams
+        // being recreated with new indices while ducked
+        let mut ducked_inputs: HashMap<String, u8> = HashMap::new();
+
         while let Ok(msg) = receiver.recv() {
+            let Some(actor) = actor.as_ref() else {
+                actor = handle_without_audio(msg, scale);
+                continue;
+            };
             match msg {
                 ActorMessage::Refresh => {
                     let state = actor.get_state();
@@ -396,6 +599,8 @@ fn run_server(socket_path: &str) -> anyhow::Result<()> {
                             continue; // Don't send success, handled above
                         }
                         CliCommand::Kill => std::process::exit(0),
+                        // The state push below is all a refresh needs
+                        CliCommand::Refresh => {}
 
                         CliCommand::SetSinkVolume { sink_index, volume } => {
                             actor.set_sink_volume(sink_index, volume)
@@ -429,6 +634,20 @@ fn run_server(socket_path: &str) -> anyhow::Result<()> {
                                 actor.set_sink_volume(def.index, (def.volume + 5).min(100));
                             }
                         }
+                        CliCommand::DuckAllInputs { percent } => {
+                            for input in actor.get_state().sink_inputs {
+                                ducked_inputs.entry(input.name).or_insert(input.volume);
+                                actor.set_input_volume(input.index, percent);
+                            }
+                        }
+                        CliCommand::RestoreAllInputs => {
+                            for input in actor.get_state().sink_inputs {
+                                if let Some(&volume) = ducked_inputs.get(&input.name) {
+                                    actor.set_input_volume(input.index, volume);
+                                }
+                            }
+                            ducked_inputs.clear();
+                        }
                         CliCommand::VolumeDown => {
                             let s = actor.get_state();
                             if let Some(def) = s.sinks.iter().find(|x| x.is_default) {
@@ -464,13 +683,11 @@ fn run_server(socket_path: &str) -> anyhow::Result<()> {
 
     // 3. LISTENER THREAD (Main)
     let listener = UnixListener::bind(socket_path)?;
-    for stream in listener.incoming() {
-        if let Ok(stream) = stream {
-            let sender = sender_sock.clone();
-            thread::spawn(move || {
-                handle_client(stream, sender);
-            });
-        }
+    for stream in listener.incoming().flatten() {
+        let sender = sender_sock.clone();
+        thread::spawn(move || {
+            handle_client(stream, sender);
+        });
     }
 
     Ok(())
@@ -481,7 +698,7 @@ fn handle_client(stream: UnixStream, sender: mpsc::Sender<ActorMessage>) {
     let mut line = String::new();
 
     // Read command
-    if let Ok(_) = reader.read_line(&mut line) {
+    if reader.read_line(&mut line).is_ok() {
         if let Ok(cmd) = serde_json::from_str::<CliCommand>(line.trim()) {
             let (tx, rx) = mpsc::channel();
 
@@ -531,7 +748,68 @@ fn send_command(socket_path: &str, cmd: CliCommand) -> anyhow::Result<()> {
 fn main() -> anyhow::Result<()> {
     let args = Cli::parse();
     match args.command {
-        CliCommand::Listen => run_server(&args.socket),
+        CliCommand::Listen {
+            volume_curve,
+            max_volume,
+            report_scale,
+        } => run_server(
+            &args.socket,
+            PercentScale {
+                curve: volume_curve,
+                report: report_scale,
+                max_volume,
+            },
+        ),
         cmd => send_command(&args.socket, cmd),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sink(index: u32, name: &str, description: &str) -> SinkInfo {
+        SinkInfo {
+            index,
+            name: name.to_string(),
+            description: description.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn default_sink_listed_second_is_the_only_one_flagged() {
+        // Mock backend listing: the default sink is enumerated second
+        let mut sinks = vec![
+            sink(1, "alsa_output.hdmi", "HDMI"),
+            sink(2, "alsa_output.analog", "Speakers"),
+            sink(3, "bluez_output.headset", "Headset"),
+        ];
+
+        mark_default(&mut sinks, Some("alsa_output.analog"), None);
+
+        let flagged: Vec<u32> = sinks
+            .iter()
+            .filter(|s| s.is_default)
+            .map(|s| s.index)
+            .collect();
+        assert_eq!(flagged, [2]);
+    }
+
+    #[test]
+    fn renamed_default_sink_is_matched_by_description() {
+        let mut sinks = vec![
+            sink(1, "alsa_output.hdmi", "HDMI"),
+            sink(2, "alsa_output.analog.2", "Speakers"),
+        ];
+
+        mark_default(&mut sinks, Some("alsa_output.analog"), Some("Speakers"));
+
+        let flagged: Vec<u32> = sinks
+            .iter()
+            .filter(|s| s.is_default)
+            .map(|s| s.index)
+            .collect();
+        assert_eq!(flagged, [2]);
+    }
+}
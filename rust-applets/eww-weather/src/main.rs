@@ -1,13 +1,172 @@
+use clap::{Parser, Subcommand, ValueEnum};
 use reqwest::blocking::Client;
-use serde::Serialize;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fs;
 use std::thread;
 use std::time::Duration;
 
-#[derive(Serialize)]
+const CACHE_FILE: &str = "/tmp/eww-weather-cache.json";
+const LOCATION_CACHE_FILE: &str = "/tmp/eww-weather-location.json";
+const GEOLOCATE_URL: &str = "https://ipinfo.io/json";
+
+#[derive(Parser)]
+#[command(name = "eww-weather", about = "Current weather for eww")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    #[arg(long, value_enum, default_value_t = Provider::Wttr)]
+    provider: Provider,
+
+    #[arg(
+        long,
+        help = "\"LAT,LON\" or a city name (required for open-meteo, optional for wttr)"
+    )]
+    location: Option<String>,
+
+    #[arg(
+        long,
+        help = "Use wttr.in's j1 JSON API instead of the one-line format (enables astronomy)"
+    )]
+    json: bool,
+
+    #[arg(
+        long,
+        help = "Without --location, look the location up from the public IP once per network and reuse it"
+    )]
+    detect_location: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Keep fetching and print a line whenever the weather changes
+    Listen {
+        /// Seconds between fetches
+        #[arg(long, default_value_t = 900, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+
+        /// Print every fetch, even when nothing changed (for widgets expecting a heartbeat)
+        #[arg(long)]
+        always_emit: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Provider {
+    Wttr,
+    OpenMeteo,
+}
+
+#[derive(Deserialize)]
+struct WttrResponse {
+    current_condition: Vec<WttrCurrent>,
+    #[serde(default)]
+    weather: Vec<WttrDay>,
+}
+
+#[derive(Deserialize)]
+struct WttrDay {
+    #[serde(default)]
+    astronomy: Vec<WttrAstronomy>,
+}
+
+#[derive(Deserialize)]
+struct WttrAstronomy {
+    sunrise: String,
+    sunset: String,
+    moon_phase: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WttrCurrent {
+    #[serde(rename = "temp_C")]
+    temp_c: String,
+    weather_desc: Vec<WttrValue>,
+}
+
+#[derive(Deserialize)]
+struct WttrValue {
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct GeocodeResponse {
+    #[serde(default)]
+    results: Vec<GeocodeResult>,
+}
+
+#[derive(Deserialize)]
+struct GeocodeResult {
+    latitude: f64,
+    longitude: f64,
+}
+
+#[derive(Deserialize)]
+struct ForecastResponse {
+    current: ForecastCurrent,
+    current_units: ForecastUnits,
+}
+
+#[derive(Deserialize)]
+struct ForecastCurrent {
+    temperature_2m: f64,
+    weather_code: u32,
+}
+
+#[derive(Deserialize)]
+struct ForecastUnits {
+    temperature_2m: String,
+}
+
+#[derive(Deserialize)]
+struct GeolocateResponse {
+    #[serde(default)]
+    city: String,
+    loc: String, // "LAT,LON"
+}
+
+/// Where the last detection put us, and on which network
+#[derive(Serialize, Deserialize)]
+struct DetectedLocation {
+    network: String,
+    city: String,
+    coordinates: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq)]
 struct Weather {
     temp: String,
     condition: String,
     icon: String,
+    // Always empty: neither wttr.in nor Open-Meteo's free API publishes alerts
+    alerts: Vec<String>,
+    sunrise: String,
+    sunset: String,
+    moon_phase: String,
+    #[serde(default)]
+    error: String, // Empty unless every attempt failed
+}
+
+/// HTTP validators of the response a cached `Weather` was parsed from
+#[derive(Serialize, Deserialize)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedWeather {
+    url: String,
+    weather: Weather,
+    validators: Validators,
+}
+
+enum Fetched {
+    NotModified(Weather),
+    Body(String, Validators),
 }
 
 fn get_icon_from_condition(condition: &str, icon_raw: &str) -> String {
@@ -33,6 +192,311 @@ fn get_icon_from_condition(condition: &str, icon_raw: &str) -> String {
     }
 }
 
+/// Map a WMO weather code (as used by Open-Meteo) to a wttr.in-style condition
+fn condition_from_wmo_code(code: u32) -> &'static str {
+    match code {
+        0 => "Clear",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 => "Fog",
+        48 => "Depositing rime fog",
+        51 => "Light drizzle",
+        53 => "Drizzle",
+        55 => "Dense drizzle",
+        56 | 57 => "Freezing drizzle",
+        61 => "Light rain",
+        63 => "Rain",
+        65 => "Heavy rain",
+        66 => "Light freezing rain",
+        67 => "Heavy freezing rain",
+        71 => "Light snow",
+        73 => "Snow",
+        75 => "Heavy snow",
+        77 => "Snow grains",
+        80 => "Light rain shower",
+        81 => "Rain shower",
+        82 => "Violent rain shower",
+        85 => "Light snow shower",
+        86 => "Heavy snow shower",
+        95 => "Thunderstorm",
+        96 | 99 => "Thunderstorm with hail",
+        _ => "Unknown",
+    }
+}
+
+/// Parse "LAT,LON" into coordinates
+fn parse_coordinates(location: &str) -> Option<(f64, f64)> {
+    let (lat, lon) = location.split_once(',')?;
+    Some((lat.trim().parse().ok()?, lon.trim().parse().ok()?))
+}
+
+/// Resolve a city name to coordinates with the Open-Meteo geocoding API
+fn geocode(client: &Client, name: &str) -> Result<Option<(f64, f64)>, reqwest::Error> {
+    let body = client
+        .get("https://geocoding-api.open-meteo.com/v1/search")
+        .query(&[("name", name), ("count", "1")])
+        .send()?
+        .text()?;
+
+    Ok(serde_json::from_str::<GeocodeResponse>(&body)
+        .ok()
+        .and_then(|r| r.results.into_iter().next())
+        .map(|r| (r.latitude, r.longitude)))
+}
+
+/// Identifies the current network by the default route's interface and gateway,
+/// plus the gateway's MAC so two routers on 192.168.1.1 still differ
+fn current_network() -> Option<String> {
+    let routes = fs::read_to_string("/proc/net/route").ok()?;
+    let (iface, gateway) = routes.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let iface = fields.next()?;
+        let destination = fields.next()?;
+        (destination == "00000000").then_some((iface, fields.next()?))
+    })?;
+    // Stored as little-endian hex
+    let gateway = u32::from_str_radix(gateway, 16).ok()?;
+    let gateway = std::net::Ipv4Addr::from(gateway.to_le_bytes()).to_string();
+
+    let mac = fs::read_to_string("/proc/net/arp")
+        .ok()
+        .and_then(|arp| {
+            arp.lines().skip(1).find_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                (fields.first() == Some(&gateway.as_str()))
+                    .then(|| fields.get(3).map(|mac| mac.to_string()))
+                    .flatten()
+            })
+        })
+        .unwrap_or_default();
+
+    Some(format!("{} {} {}", iface, gateway, mac))
+}
+
+fn geolocate(client: &Client) -> Result<Option<GeolocateResponse>, reqwest::Error> {
+    let body = client.get(GEOLOCATE_URL).send()?.text()?;
+    Ok(serde_json::from_str::<GeolocateResponse>(&body)
+        .ok()
+        .filter(|r| parse_coordinates(&r.loc).is_some()))
+}
+
+/// The detected location, looked up again only when the network changed.
+/// Falls back to the last detection when the lookup fails.
+fn detect_location(client: &Client) -> Option<DetectedLocation> {
+    let cached = fs::read_to_string(LOCATION_CACHE_FILE)
+        .ok()
+        .and_then(|data| serde_json::from_str::<DetectedLocation>(&data).ok());
+    let network = current_network().unwrap_or_default();
+    if cached.as_ref().is_some_and(|c| c.network == network) {
+        return cached;
+    }
+
+    match geolocate(client) {
+        Ok(Some(found)) => {
+            let detected = DetectedLocation {
+                network,
+                city: found.city,
+                coordinates: found.loc,
+            };
+            if let Ok(json) = serde_json::to_string(&detected) {
+                let _ = fs::write(LOCATION_CACHE_FILE, json);
+            }
+            Some(detected)
+        }
+        Ok(None) => cached,
+        Err(e) => {
+            eprintln!("Location detection failed: {}", e);
+            cached
+        }
+    }
+}
+
+fn parse_wttr_line(data: &str) -> Option<Weather> {
+    let parts: Vec<&str> = data.split('|').collect();
+
+    if parts.len() < 3 {
+        return None;
+    }
+
+    let temp = parts[0].trim().to_string();
+    let condition = parts[1].trim().to_string();
+    let icon_raw = parts[2].trim();
+    let icon = get_icon_from_condition(&condition, icon_raw);
+
+    Some(Weather {
+        temp,
+        condition,
+        icon,
+        alerts: Vec::new(),
+        sunrise: String::new(),
+        sunset: String::new(),
+        moon_phase: String::new(),
+        error: String::new(),
+    })
+}
+
+fn parse_wttr_json(body: &str) -> Option<Weather> {
+    let response = serde_json::from_str::<WttrResponse>(body).ok()?;
+    let current = response.current_condition.into_iter().next()?;
+    // Today's entry comes first
+    let (sunrise, sunset, moon_phase) = response
+        .weather
+        .into_iter()
+        .next()
+        .and_then(|day| day.astronomy.into_iter().next())
+        .map(|a| (a.sunrise, a.sunset, a.moon_phase))
+        .unwrap_or_default();
+
+    // Same shape as the one-line %t, e.g. "+12°C"
+    let temp = match current.temp_c.trim().parse::<i32>() {
+        Ok(t) => format!("{:+}°C", t),
+        Err(_) => format!("{}°C", current.temp_c.trim()),
+    };
+    let condition = current
+        .weather_desc
+        .into_iter()
+        .next()
+        .map(|d| d.value.trim().to_string())
+        .unwrap_or_default();
+    let icon = get_icon_from_condition(&condition, "");
+
+    Some(Weather {
+        temp,
+        condition,
+        icon,
+        alerts: Vec::new(),
+        sunrise,
+        sunset,
+        moon_phase,
+        error: String::new(),
+    })
+}
+
+fn load_cache(url: &str) -> Option<CachedWeather> {
+    let data = fs::read_to_string(CACHE_FILE).ok()?;
+    serde_json::from_str::<CachedWeather>(&data)
+        .ok()
+        .filter(|cached| cached.url == url)
+}
+
+fn save_cache(url: &str, weather: Weather, validators: Validators) -> Weather {
+    let cached = CachedWeather {
+        url: url.to_string(),
+        weather,
+        validators,
+    };
+    if let Ok(json) = serde_json::to_string(&cached) {
+        let _ = fs::write(CACHE_FILE, json);
+    }
+    cached.weather
+}
+
+/// GET `url`, revalidating against the cached response when there is one
+fn conditional_get(client: &Client, url: &str) -> Result<Fetched, reqwest::Error> {
+    let cached = load_cache(url);
+    let mut request = client.get(url);
+    if let Some(validators) = cached.as_ref().map(|c| &c.validators) {
+        if let Some(etag) = &validators.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = request.send()?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some(cached) = cached {
+            return Ok(Fetched::NotModified(cached.weather));
+        }
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+    let validators = Validators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    };
+    Ok(Fetched::Body(response.text()?, validators))
+}
+
+fn fetch_wttr(
+    client: &Client,
+    location: Option<&str>,
+    json: bool,
+) -> Result<Option<Weather>, reqwest::Error> {
+    let format = if json { "j1" } else { "%t|%C|%c" };
+    let url = format!(
+        "https://wttr.in/{}?format={}",
+        location.unwrap_or_default(),
+        format
+    );
+
+    match conditional_get(client, &url)? {
+        Fetched::NotModified(weather) => Ok(Some(weather)),
+        Fetched::Body(body, validators) => {
+            let weather = if json {
+                parse_wttr_json(&body)
+            } else {
+                parse_wttr_line(&body)
+            };
+            Ok(weather.map(|w| save_cache(&url, w, validators)))
+        }
+    }
+}
+
+fn fetch_open_meteo(client: &Client, location: &str) -> Result<Option<Weather>, reqwest::Error> {
+    let coords = match parse_coordinates(location) {
+        Some(coords) => coords,
+        None => match geocode(client, location)? {
+            Some(coords) => coords,
+            None => {
+                eprintln!("Could not geocode location '{}'", location);
+                return Ok(None);
+            }
+        },
+    };
+
+    let body = client
+        .get("https://api.open-meteo.com/v1/forecast")
+        .query(&[
+            ("latitude", coords.0.to_string()),
+            ("longitude", coords.1.to_string()),
+            ("current", "temperature_2m,weather_code".to_string()),
+        ])
+        .send()?
+        .text()?;
+
+    let Ok(forecast) = serde_json::from_str::<ForecastResponse>(&body) else {
+        return Ok(None);
+    };
+
+    // Same shape as wttr.in's %t, e.g. "+12°C"
+    let temp = format!(
+        "{:+.0}{}",
+        forecast.current.temperature_2m, forecast.current_units.temperature_2m
+    );
+    let condition = condition_from_wmo_code(forecast.current.weather_code).to_string();
+    let icon = get_icon_from_condition(&condition, "");
+
+    Ok(Some(Weather {
+        temp,
+        condition,
+        icon,
+        alerts: Vec::new(),
+        sunrise: String::new(),
+        sunset: String::new(),
+        moon_phase: String::new(),
+        error: String::new(),
+    }))
+}
+
 /// Check if internet is available by pinging a reliable host
 fn check_internet() -> bool {
     let client = Client::builder()
@@ -55,37 +519,50 @@ fn check_internet() -> bool {
     false
 }
 
-fn fetch_weather_with_retry() -> Weather {
+fn fetch_weather_with_retry(cli: &Cli) -> Weather {
     let client = Client::builder()
         .timeout(Duration::from_secs(5))
         .build()
         .unwrap();
 
+    // An explicit --location always wins over detection
+    let detected = match &cli.location {
+        None if cli.detect_location => detect_location(&client),
+        _ => None,
+    };
+    let location = cli.location.clone().or_else(|| {
+        detected.map(|d| match cli.provider {
+            // wttr.in names the city in its answers, Open-Meteo needs no geocoding
+            Provider::Wttr if !d.city.is_empty() => d.city,
+            _ => d.coordinates,
+        })
+    });
+
     let max_retries = 10;
     let mut retry_delay = Duration::from_secs(1);
+    let mut error = "No usable response".to_string();
 
     for attempt in 0..max_retries {
-        match client.get("https://wttr.in/?format=%t|%C|%c").send() {
-            Ok(response) => {
-                if let Ok(data) = response.text() {
-                    let parts: Vec<&str> = data.split('|').collect();
-
-                    if parts.len() >= 3 {
-                        let temp = parts[0].trim().to_string();
-                        let condition = parts[1].trim().to_string();
-                        let icon_raw = parts[2].trim();
-                        let icon = get_icon_from_condition(&condition, icon_raw);
-
-                        return Weather {
-                            temp,
-                            condition,
-                            icon,
-                        };
-                    }
+        let result = match cli.provider {
+            Provider::Wttr => fetch_wttr(&client, location.as_deref(), cli.json),
+            Provider::OpenMeteo => match location.as_deref() {
+                Some(location) => fetch_open_meteo(&client, location),
+                None => {
+                    error =
+                        "--location or --detect-location is required for the open-meteo provider"
+                            .to_string();
+                    eprintln!("{}", error);
+                    break;
                 }
-            }
+            },
+        };
+
+        match result {
+            Ok(Some(weather)) => return weather,
+            Ok(None) => {}
             Err(e) => {
                 eprintln!("Weather fetch attempt {} failed: {}", attempt + 1, e);
+                error = e.to_string();
 
                 // Only retry if we have internet connectivity and attempts remain
                 if attempt < max_retries - 1 && check_internet() {
@@ -93,6 +570,7 @@ fn fetch_weather_with_retry() -> Weather {
                     thread::sleep(retry_delay);
                     retry_delay *= 2; // Exponential backoff
                 } else if !check_internet() {
+                    error = "No internet connection".to_string();
                     eprintln!("No internet connection detected");
                     break;
                 }
@@ -104,10 +582,38 @@ fn fetch_weather_with_retry() -> Weather {
         temp: "".to_string(),
         condition: "No data".to_string(),
         icon: "󰖐".to_string(),
+        alerts: Vec::new(),
+        sunrise: String::new(),
+        sunset: String::new(),
+        moon_phase: String::new(),
+        error,
+    }
+}
+
+fn listen(cli: &Cli, interval: u64, always_emit: bool) {
+    let mut last: Option<Weather> = None;
+
+    loop {
+        let weather = fetch_weather_with_retry(cli);
+        if always_emit || last.as_ref() != Some(&weather) {
+            println!("{}", serde_json::to_string(&weather).unwrap());
+            last = Some(weather);
+        }
+        thread::sleep(Duration::from_secs(interval));
     }
 }
 
 fn main() {
-    let weather = fetch_weather_with_retry();
-    println!("{}", serde_json::to_string(&weather).unwrap());
+    let cli = Cli::parse();
+
+    match cli.command {
+        Some(Commands::Listen {
+            interval,
+            always_emit,
+        }) => listen(&cli, interval, always_emit),
+        None => {
+            let weather = fetch_weather_with_retry(&cli);
+            println!("{}", serde_json::to_string(&weather).unwrap());
+        }
+    }
 }
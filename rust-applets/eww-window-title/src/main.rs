@@ -1,6 +1,29 @@
+use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
 use swayipc::{Connection, Event, EventType, Node};
-use std::env;
+
+const RECONNECT_DELAY_MS: u64 = 1000;
+const SETTLE_DELAY_MS: u64 = 50; // Lets window state stabilize after an event
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the title whenever the focused window changes
+    Listen {
+        /// Minimum time between two printed lines
+        #[arg(long, default_value_t = 0)]
+        min_interval_ms: u64,
+    },
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct WindowTitle {
@@ -43,38 +66,121 @@ fn get_window_title() -> Result<String, Box<dyn std::error::Error>> {
     Ok(truncated)
 }
 
+fn render_title(title: String) -> Option<String> {
+    serde_json::to_string(&WindowTitle { title }).ok()
+}
+
 fn output_title() {
-    if let Ok(title) = get_window_title() {
-        let info = WindowTitle { title };
-        if let Ok(json) = serde_json::to_string(&info) {
-            println!("{}", json);
-        }
+    if let Some(json) = get_window_title().ok().and_then(render_title) {
+        println!("{}", json);
     }
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = env::args().collect();
-    let listen_mode = args.len() > 1 && args[1] == "listen";
+// Listen-mode printer: only emits when the rendered title changed, and
+// never more often than `min_interval`. Events inside the interval are
+// coalesced into one render when it expires.
+struct TitleEmitter {
+    last_line: Option<String>,
+    last_emit: Option<Instant>,
+    min_interval: Duration,
+}
 
-    if listen_mode {
-        // Output initial title
-        output_title();
+impl TitleEmitter {
+    fn new(min_interval: Duration) -> Self {
+        TitleEmitter {
+            last_line: None,
+            last_emit: None,
+            min_interval,
+        }
+    }
+
+    /// Time left before another line may be printed
+    fn until_allowed(&self) -> Duration {
+        self.last_emit
+            .map(|at| self.min_interval.saturating_sub(at.elapsed()))
+            .unwrap_or_default()
+    }
+
+    fn emit(&mut self) {
+        let Some(line) = get_window_title().ok().and_then(render_title) else {
+            return;
+        };
+        if self.last_line.as_ref() == Some(&line) {
+            return;
+        }
+
+        println!("{}", line);
+        self.last_line = Some(line);
+        self.last_emit = Some(Instant::now());
+    }
 
-        // Subscribe to window events
-        let events = Connection::new()?.subscribe(&[EventType::Window])?;
+    /// Render for window events from `events` until the channel closes
+    fn run(&mut self, events: &Receiver<()>) {
+        let mut pending = false;
+        loop {
+            let received = if pending {
+                events.recv_timeout(self.until_allowed())
+            } else {
+                events.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            };
+            match received {
+                Ok(()) => {
+                    thread::sleep(Duration::from_millis(SETTLE_DELAY_MS));
+                    while events.try_recv().is_ok() {}
+                    pending = true;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
 
-        // Listen for events
+            if pending && self.until_allowed().is_zero() {
+                self.emit();
+                pending = false;
+            }
+        }
+    }
+}
+
+// Forwards window events from a sway subscription until it ends
+fn spawn_event_reader(conn: Connection) -> swayipc::Fallible<Receiver<()>> {
+    let events = conn.subscribe([EventType::Window])?;
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
         for event in events {
             match event {
-                Ok(Event::Window(_)) => {
-                    // Small delay to let window state stabilize
-                    std::thread::sleep(std::time::Duration::from_millis(50));
-                    output_title();
+                Ok(Event::Window(_)) if tx.send(()).is_err() => break,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    break;
                 }
-                Err(e) => eprintln!("Error: {}", e),
                 _ => {}
             }
         }
+    });
+    Ok(rx)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
+    if let Some(Command::Listen { min_interval_ms }) = cli.command {
+        let mut emitter = TitleEmitter::new(Duration::from_millis(min_interval_ms));
+
+        // Re-subscribe whenever the IPC connection drops (e.g. sway restart)
+        // and refresh the current title so the widget never goes stale
+        loop {
+            emitter.emit();
+
+            match Connection::new().and_then(spawn_event_reader) {
+                Ok(events) => {
+                    emitter.run(&events);
+                    eprintln!("Lost sway IPC connection, reconnecting");
+                }
+                Err(e) => eprintln!("Failed to subscribe to window events: {}", e),
+            }
+
+            thread::sleep(Duration::from_millis(RECONNECT_DELAY_MS));
+        }
     } else {
         // One-shot mode
         output_title();
@@ -1,6 +1,26 @@
+use clap::Parser;
 use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::Duration;
 use swayipc::{Connection, EventType};
 
+const CONNECT_ATTEMPTS: u32 = 5;
+const CONNECT_BACKOFF_MS: u64 = 200;
+const RECONNECT_DELAY_MS: u64 = 1000;
+
+#[derive(Parser)]
+#[command(name = "eww-workspaces", about = "Sway workspaces for eww")]
+struct Cli {
+    /// Leave the scratchpad out of the list instead of flagging it
+    #[arg(long)]
+    hide_scratchpad: bool,
+
+    /// Wrap the list as {"focused_output", "workspaces"} so each monitor's
+    /// bar can tell whether it is the active one
+    #[arg(long)]
+    with_context: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct WorkspaceInfo {
     num: i32,
@@ -9,13 +29,71 @@ struct WorkspaceInfo {
     focused: bool,
     urgent: bool,
     output: String,
+    is_scratchpad: bool,
+}
+
+// Printed instead of the bare list with --with-context
+#[derive(Debug, Serialize)]
+struct WorkspacesContext {
+    focused_output: String, // Empty when sway reports no focused output
+    workspaces: Vec<WorkspaceInfo>,
+}
+
+// Sway may still be starting when the bar launches us, so retry with a
+// doubling backoff before giving up
+fn connect_with_retry() -> Option<Connection> {
+    let mut delay = Duration::from_millis(CONNECT_BACKOFF_MS);
+    for attempt in 1..=CONNECT_ATTEMPTS {
+        match Connection::new() {
+            Ok(conn) => return Some(conn),
+            Err(e) => {
+                eprintln!(
+                    "Failed to connect to sway ({}/{}): {}",
+                    attempt, CONNECT_ATTEMPTS, e
+                );
+                if attempt < CONNECT_ATTEMPTS {
+                    thread::sleep(delay);
+                    delay *= 2;
+                }
+            }
+        }
+    }
+    None
+}
+
+// Sway reports its scratchpad as a workspace named "__i3_scratch". Its num
+// (-1) doesn't identify it: every workspace not named after a number has -1.
+fn is_scratchpad(name: &str) -> bool {
+    name.starts_with("__i3_scratch")
+}
+
+// Sway gives named workspaces like "1:web" num -1 in some setups, so fall back
+// to the name's leading digits
+fn sort_number(ws: &WorkspaceInfo) -> Option<i32> {
+    if ws.num >= 0 {
+        return Some(ws.num);
+    }
+    let digits: String = ws.name.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+// Sway lists workspaces in focus order; this keeps the button row fixed:
+// numbered workspaces ascending, then unnumbered ones by name, then the scratchpad
+fn sort_workspaces(workspaces: &mut [WorkspaceInfo]) {
+    workspaces.sort_by(|a, b| {
+        let key =
+            |ws: &WorkspaceInfo| (ws.is_scratchpad, sort_number(ws).is_none(), sort_number(ws));
+        key(a).cmp(&key(b)).then_with(|| a.name.cmp(&b.name))
+    });
 }
 
-fn get_workspaces() -> Result<Vec<WorkspaceInfo>, Box<dyn std::error::Error>> {
-    let mut conn = Connection::new()?;
+fn get_workspaces(
+    conn: &mut Connection,
+    cli: &Cli,
+) -> Result<Vec<WorkspaceInfo>, Box<dyn std::error::Error>> {
     let workspaces = conn.get_workspaces()?;
 
-    Ok(workspaces
+    let mut workspaces: Vec<WorkspaceInfo> = workspaces
         .iter()
         .map(|ws| WorkspaceInfo {
             num: ws.num,
@@ -24,29 +102,114 @@ fn get_workspaces() -> Result<Vec<WorkspaceInfo>, Box<dyn std::error::Error>> {
             focused: ws.focused,
             urgent: ws.urgent,
             output: ws.output.clone(),
+            is_scratchpad: is_scratchpad(&ws.name),
         })
-        .collect())
+        .filter(|ws| !(cli.hide_scratchpad && ws.is_scratchpad))
+        .collect();
+    sort_workspaces(&mut workspaces);
+    Ok(workspaces)
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Output initial state
-    let workspaces = get_workspaces()?;
-    println!("{}", serde_json::to_string(&workspaces)?);
+fn get_focused_output(conn: &mut Connection) -> Result<String, Box<dyn std::error::Error>> {
+    let outputs = conn.get_outputs()?;
+    Ok(outputs
+        .into_iter()
+        .find(|output| output.focused)
+        .map(|output| output.name)
+        .unwrap_or_default())
+}
+
+fn render(cli: &Cli) -> Result<String, Box<dyn std::error::Error>> {
+    let Some(mut conn) = connect_with_retry() else {
+        return Ok(if cli.with_context {
+            serde_json::to_string(&WorkspacesContext {
+                focused_output: String::new(),
+                workspaces: Vec::new(),
+            })?
+        } else {
+            "[]".to_string()
+        });
+    };
+
+    let workspaces = get_workspaces(&mut conn, cli)?;
+    if !cli.with_context {
+        return Ok(serde_json::to_string(&workspaces)?);
+    }
+    Ok(serde_json::to_string(&WorkspacesContext {
+        focused_output: get_focused_output(&mut conn)?,
+        workspaces,
+    })?)
+}
+
+fn emit_workspaces(cli: &Cli) {
+    match render(cli) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("Error: {}", e),
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
 
-    // Subscribe to workspace events
-    let events = Connection::new()?
-        .subscribe(&[EventType::Workspace])?;
+    // Sway restarts drop the subscription, so reconnect and re-emit the
+    // current state whenever the event stream ends
+    loop {
+        emit_workspaces(&cli);
 
-    // Listen for events
-    for event in events {
-        match event {
-            Ok(_) => {
-                let workspaces = get_workspaces()?;
-                println!("{}", serde_json::to_string(&workspaces)?);
+        if let Some(conn) = connect_with_retry() {
+            // Output events cover monitors being plugged in or removed
+            match conn.subscribe([EventType::Workspace, EventType::Output]) {
+                Ok(events) => {
+                    for event in events {
+                        match event {
+                            Ok(_) => emit_workspaces(&cli),
+                            Err(e) => {
+                                eprintln!("Error: {}", e);
+                                break;
+                            }
+                        }
+                    }
+                    eprintln!("Lost sway IPC connection, reconnecting");
+                }
+                Err(e) => eprintln!("Failed to subscribe to workspace events: {}", e),
             }
-            Err(e) => eprintln!("Error: {}", e),
         }
+
+        thread::sleep(Duration::from_millis(RECONNECT_DELAY_MS));
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(())
+    fn workspace(num: i32, name: &str) -> WorkspaceInfo {
+        WorkspaceInfo {
+            num,
+            name: name.to_string(),
+            visible: false,
+            focused: false,
+            urgent: false,
+            output: "eDP-1".to_string(),
+            is_scratchpad: is_scratchpad(name),
+        }
+    }
+
+    #[test]
+    fn numbers_parsed_from_names_sort_with_numbered_workspaces() {
+        let mut workspaces = vec![
+            workspace(-1, "__i3_scratch"),
+            workspace(-1, "chat"),
+            workspace(3, "3"),
+            workspace(-1, "1:web"),
+            workspace(-1, "alpha"),
+            workspace(2, "2"),
+        ];
+
+        sort_workspaces(&mut workspaces);
+
+        let names: Vec<&str> = workspaces.iter().map(|ws| ws.name.as_str()).collect();
+        assert_eq!(names, ["1:web", "2", "3", "alpha", "chat", "__i3_scratch"]);
+        assert!(!workspaces[0].is_scratchpad);
+    }
 }